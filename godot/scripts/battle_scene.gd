@@ -27,16 +27,32 @@ var simulator: Match
 @export var graphics : bool = true
 @export var player1_bot: bool = false
 @export var player2_bot: bool = false
+@export var player1_bot_kind: BotKind = BotKind.Punisher
+@export var player2_bot_kind: BotKind = BotKind.Punisher
+@export var player1_bot_difficulty: Difficulty = Difficulty.Perfect
+@export var player2_bot_difficulty: Difficulty = Difficulty.Perfect
+# Seeds the bots of player 1 and, plus one, of player 2, so a match plays out the same again.
+@export var bot_seed: int = 0
 
 var p1_input_type: PlayerType
 var p2_input_type: PlayerType
+var p1_bot: Bot
+var p2_bot: Bot
 
 # Called when the node enters the scene tree for the first time.
 func _ready():
-	simulator = Match.gd_new(player1_bot, player1_bot)
+	simulator = Match.gd_new(player1_bot, player2_bot)
 	
 	p1_input_type = PlayerType.Player1 if !player1_bot else PlayerType.Ai1
 	p2_input_type = PlayerType.Player2 if !player2_bot else PlayerType.Ai2
+	if player1_bot:
+		p1_bot = Bot.gd_new(player1_bot_kind, player1_bot_difficulty, true, bot_seed)
+		player1_input_dummy.bot = p1_bot
+		player1_input_dummy.battle = self
+	if player2_bot:
+		p2_bot = Bot.gd_new(player2_bot_kind, player2_bot_difficulty, false, bot_seed + 1)
+		player2_input_dummy.bot = p2_bot
+		player2_input_dummy.battle = self
 
 func _process(_delta: float) -> void:
 	var stage_size = stage_borders.size
@@ -57,8 +73,8 @@ func _network_postprocess(_input: Dictionary) -> void:
 	# var start = Time.get_ticks_usec()
 	var res := simulator.frame_update(p1_input, p2_input)
 	var cont := res == Result.Continue || res == Result.Pause
-	for audio in simulator.audio():
-		play_audio(audio)
+	for sound in simulator.audio():
+		play_audio(sound)
 	
 	# var end = Time.get_ticks_usec()
 	# print(end - start)
@@ -85,6 +101,23 @@ enum Result {
 	Timeout,
 }
 
+# Same order as `BotKind` in the simulator.
+enum BotKind {
+	Idle,
+	Random,
+	Turtle,
+	Punisher,
+	Search,
+}
+
+# Same order as `Difficulty` in the simulator.
+enum Difficulty {
+	Perfect,
+	Hard,
+	Normal,
+	Easy,
+}
+
 enum PlayerType {
 	Player1,
 	Player2,
@@ -92,24 +125,21 @@ enum PlayerType {
 	Ai2,
 }
 
+# Bots send their inputs through the input dummies too, see `PlayerInputDummy.bot`.
 func get_inputs(type: PlayerType) -> FgInput:
 		match type:
-			PlayerType.Player1:
+			PlayerType.Player1, PlayerType.Ai1:
 				return player1_input_dummy.NetInput
-			PlayerType.Player2:
+			PlayerType.Player2, PlayerType.Ai2:
 				return player2_input_dummy.NetInput
 			_:
 				assert(false, "wait what???")
 				return null
 
-func play_audio(audio_id: String):
-	var split := audio_id.split(":")
-	var _player_id := split[0]
-	var audio_path := split[1]
-	
-	var audio_stream = load("res://audio/" + audio_path + ".wav") as AudioStream
+func play_audio(sound: SoundEvent):
+	var audio_stream = load("res://audio/" + sound.name() + ".wav") as AudioStream
 	if audio_stream:
-		SyncManager.play_sound(audio_id, audio_stream)
+		SyncManager.play_sound(sound.id(), audio_stream)
 
 func round_ui_update(p1: int, p2: int):
 	p1_round3.texture = round1 if p1 >= 3 else round0
@@ -11,17 +11,30 @@ func _ready():
 func _process(delta):
 	queue_redraw()
 
-var collision_p1 = Vector2.DOWN
-var collision_p2 = Vector2.DOWN
-var collision_size = Vector2(125, -153)
+var ground = Vector2(0, 500)
 var collision_color = Color(0.3, 0.3, 0.3, 0.7)
-
-var hurtbox_p1 = []
-var hurtbox_p2 = []
-var hurtbox_color = Color(0.3, 0.3, 0.3, 0.7)
+var hurtbox_color = Color(0.2, 0.4, 1.0, 0.5)
+var hitbox_color = Color(1.0, 0.2, 0.2, 0.6)
 
 # Called every frame to draw
 func _draw():
-	draw_rect(Rect2(collision_p1 + Vector2(400, 500), collision_size * Vector2(1, 1)), collision_color)
-	draw_rect(Rect2(collision_p2 + Vector2(400, 500), collision_size * Vector2(-1, 1)), collision_color)
-	
+	var simulator: Match = battle_scene.simulator
+	if simulator == null:
+		return
+
+	for p1 in [true, false]:
+		draw_box(simulator.collision_box(p1), collision_color)
+		for hurtbox in simulator.hurtboxes(p1):
+			draw_box(hurtbox, hurtbox_color)
+
+		var hitbox := simulator.hitbox(p1)
+		if hitbox.has_area():
+			draw_box(hitbox, hitbox_color)
+
+# Boxes are in stage units with the ground at y = 0, only x is scaled like the player sprites.
+func draw_box(box: Rect2i, color: Color):
+	var scale_x: float = battle_scene.stage_borders.size.x / 1872.0
+	var position := Vector2((box.position.x + battle_scene.STAGE_START) * scale_x, box.position.y)
+	var size := Vector2(box.size.x * scale_x, box.size.y)
+
+	draw_rect(Rect2(ground + position, size), color)
@@ -6,8 +6,19 @@ class_name PlayerInputDummy
 var steam_mp_id: int = 1 # Setting this to 0 breaks inputs
 var input_prefix := "p1_"
 var NetInput :FgInput = null
+# Plays instead of the keyboard when set. It is only asked here, once per tick, so rollbacks
+# replay its inputs rather than running it again.
+var bot: Bot = null
+var battle: BattleScene = null
 
 func _get_local_input() -> Dictionary:
+	if bot:
+		var bot_input := bot.input(battle.simulator)
+		return {"movement": bot_input.movement(),
+				"attack_press": bot_input.attack_press(),
+				"special_press": bot_input.special_press(),
+		}
+	
 	var movement := (Input.is_action_pressed(prefix("forward")) as int) - (Input.is_action_pressed(prefix("backward")) as int)
 	var attack_press := Input.is_action_just_pressed(prefix("attack"))
 	var special_press := Input.is_action_just_pressed(prefix("special"))
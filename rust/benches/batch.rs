@@ -0,0 +1,83 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use footsies_sim::{
+	batch::BatchEnv,
+	env::{action_input, ACTION_COUNT},
+	rng::Rng,
+	simul::{Match, Result},
+};
+
+const MATCHES: usize = 4096;
+const FRAMES: usize = 64;
+
+fn actions() -> Vec<Vec<u8>> {
+	let mut rng = Rng::new(0);
+
+	(0..FRAMES)
+		.map(|_| {
+			(0..MATCHES * 2)
+				.map(|_| rng.below(ACTION_COUNT as u32) as u8)
+				.collect()
+		})
+		.collect()
+}
+
+fn batch(c: &mut Criterion) {
+	let actions = actions();
+	let mut group = c.benchmark_group("step");
+	group.throughput(Throughput::Elements((MATCHES * FRAMES) as u64));
+
+	group.bench_function("frame_update", |b| {
+		b.iter_batched_ref(
+			|| vec![Match::new(false, false); MATCHES],
+			|matches| {
+				for frame in &actions {
+					for (sim, actions) in
+						matches.iter_mut().zip(frame.chunks_exact(2))
+					{
+						let res = sim.frame_update(
+							action_input(actions[0]),
+							action_input(actions[1]),
+						);
+
+						if !matches!(res, Result::Continue | Result::Pause)
+						{
+							sim.new_round();
+							if !sim.continues() {
+								*sim = Match::new(false, false);
+							}
+						}
+					}
+				}
+			},
+			BatchSize::LargeInput,
+		)
+	});
+
+	group.bench_function("batch", |b| {
+		let mut env = BatchEnv::new(Match::new(false, false), MATCHES);
+		env.reset(0);
+
+		b.iter(|| {
+			for frame in &actions {
+				env.step(frame);
+			}
+		})
+	});
+
+	#[cfg(feature = "parallel")]
+	group.bench_function("batch_parallel", |b| {
+		let mut env = BatchEnv::new(Match::new(false, false), MATCHES).with_parallel(true);
+		env.reset(0);
+
+		b.iter(|| {
+			for frame in &actions {
+				env.step(frame);
+			}
+		})
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, batch);
+criterion_main!(benches);
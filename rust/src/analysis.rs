@@ -0,0 +1,171 @@
+//! Timing and reach of moves derived from their frame data, for bots and tooling.
+//!
+//! Frames are counted from `0`, the frame a move starts on. Distances are between player
+//! positions, with the attacker facing the opponent.
+
+use crate::{
+	cbox::CBox,
+	framedata::{move_data, move_length, MoveData},
+};
+
+/// Timing and reach of an attack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackData {
+	/// Frames before the first one with a hitbox.
+	pub startup: u8,
+	/// Frames from the first to the last one with a hitbox.
+	pub active: u8,
+	/// Frames after the last one with a hitbox.
+	pub recovery: u8,
+	/// How far in front of where the attacker started the hitbox reaches, movement included.
+	pub range: i16,
+}
+
+impl AttackData {
+	/// `None` for moves without a hitbox.
+	pub fn new(frames: &[MoveData]) -> Option<Self> {
+		let len = move_length(frames);
+		let mut active = None;
+		let mut offset = 0;
+		let mut range = i16::MIN;
+
+		for frame in 0..len {
+			let data = &move_data(frame, frames)?.data;
+			offset += data.speed;
+
+			if let Some(hitbox) = data.hitbox {
+				let (first, _) = active.unwrap_or((frame, frame));
+				active = Some((first, frame));
+				range = range.max(offset
+					+ hitbox.offset_x.max(hitbox.offset_x + hitbox.width));
+			}
+		}
+
+		let (first, last) = active?;
+		Some(AttackData {
+			startup: first,
+			active: last - first + 1,
+			recovery: len - last - 1,
+			range,
+		})
+	}
+
+	#[inline]
+	pub const fn total(self) -> u8 {
+		self.startup + self.active + self.recovery
+	}
+
+	/// Frame advantage of the attacker after its last active frame misses. Never positive, the
+	/// opponent can act for `recovery` frames before the attacker can.
+	#[inline]
+	pub const fn whiff_advantage(self) -> i16 {
+		-(self.recovery as i16)
+	}
+
+	/// Furthest distance at which the attack reaches `hurtbox` of a standing opponent, heights
+	/// left out.
+	#[inline]
+	pub fn max_range(self, hurtbox: CBox) -> i16 {
+		self.range + hurtbox.offset_x.max(hurtbox.offset_x + hurtbox.width) - 1
+	}
+}
+
+/// Whether `attack` started on the next frame hits an opponent `distance` away before it is done
+/// with `target`, which it is on `frame` of and cannot act out of. Getting hit by `target` first or
+/// on the same frame is no punish. Both players move as their frame data says and push each other
+/// like in a match, the stage edges are left out.
+pub fn can_punish(attack: &[MoveData], target: &[MoveData], frame: u8, distance: i16) -> bool {
+	let mut attacker = 0;
+	let mut defender = distance;
+
+	for k in 0..move_length(attack) {
+		let Some(a) = move_data(k, attack) else {
+			return false;
+		};
+		let Some(t) = frame
+			.checked_add(k + 1)
+			.and_then(|frame| move_data(frame, target))
+		else {
+			return false;
+		};
+
+		attacker += a.data.speed;
+		defender -= t.data.speed;
+
+		let (a_col, t_col) = (a.data.collision, t.data.collision);
+		if a_col.overlap(attacker, -t_col, defender) {
+			let amount = a_col.overlap_amount(attacker, -t_col, defender);
+			attacker -= amount;
+			defender += amount;
+		}
+
+		let hits = |hitbox: Option<CBox>, hurtbox: &[Option<CBox>], mirrored: bool| {
+			hitbox.is_some_and(|hit| {
+				hurtbox.iter().flatten().any(|&hurt| match mirrored {
+					true => (-hit).overlap(defender, hurt, attacker),
+					false => hit.overlap(attacker, -hurt, defender),
+				})
+			})
+		};
+		if hits(t.data.hitbox, &a.data.hurtbox, true) {
+			return false;
+		}
+		if hits(a.data.hitbox, &t.data.hurtbox, false) {
+			return true;
+		}
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::character::CharacterId;
+
+	#[test]
+	fn attack_data() {
+		let moves = &CharacterId::DEFAULT.get().moves;
+
+		let nnormal = AttackData::new(&moves.nnormal).unwrap();
+		assert_eq!(
+			nnormal,
+			AttackData {
+				startup: 5,
+				active: 2,
+				recovery: 16,
+				range: 299
+			}
+		);
+		assert_eq!(nnormal.total(), move_length(&moves.nnormal));
+		assert_eq!(nnormal.whiff_advantage(), -16);
+		assert_eq!(nnormal.max_range(CBox::base_hurtbox()), 299 + 158 - 1);
+
+		let nspecial = AttackData::new(&moves.nspecial).unwrap();
+		assert_eq!(nspecial.startup, 12);
+		assert_eq!(nspecial.range, 304 + 2 * 4 + 260);
+
+		assert_eq!(AttackData::new(&moves.idle), None);
+	}
+
+	#[test]
+	fn punish() {
+		let moves = &CharacterId::DEFAULT.get().moves;
+		let nnormal = AttackData::new(&moves.nnormal).unwrap();
+		let last = nnormal.total() - 1;
+
+		// The last frames of a whiffed normal are too late to punish with its own startup.
+		let whiffed = nnormal.startup + nnormal.active;
+		assert!(can_punish(&moves.nnormal, &moves.nnormal, whiffed, 300));
+		assert!(!can_punish(
+			&moves.nnormal,
+			&moves.nnormal,
+			last - nnormal.startup,
+			300
+		));
+		assert!(!can_punish(&moves.nnormal, &moves.nnormal, last, 300));
+
+		// Out of range of the extended hurtbox of the recovery.
+		assert!(!can_punish(&moves.nnormal, &moves.nnormal, whiffed, 700));
+	}
+}
@@ -0,0 +1,247 @@
+//! Lockstep simulation of many [`Env`]s for self-play training.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+	env::{Env, OBS_LEN},
+	simul::Match,
+};
+
+/// Observation values per match, player 1 followed by player 2.
+pub const MATCH_OBS_LEN: usize = 2 * OBS_LEN;
+
+/// Owns `len` environments and their output buffers. Every buffer is laid out match by match with
+/// player 1 first, so the observations are one contiguous `[len, 2, OBS_LEN]` array.
+///
+/// Finished episodes are continued right away with [`Env::next_round`], so the observation written
+/// for a `done` match is already the first one of its next episode.
+#[derive(Debug, Clone)]
+pub struct BatchEnv {
+	envs: Vec<Env>,
+	obs: Vec<f32>,
+	rewards: Vec<f32>,
+	dones: Vec<bool>,
+	new_matches: Vec<bool>,
+	#[cfg(feature = "parallel")]
+	parallel: bool,
+}
+
+impl BatchEnv {
+	pub fn new(initial: Match, len: usize) -> Self {
+		BatchEnv {
+			envs: vec![Env::new(initial); len],
+			obs: vec![0.0; len * MATCH_OBS_LEN],
+			rewards: vec![0.0; len * 2],
+			dones: vec![false; len],
+			new_matches: vec![false; len],
+			#[cfg(feature = "parallel")]
+			parallel: false,
+		}
+	}
+
+	/// See [`Env::with_noop_max`].
+	pub fn with_noop_max(self, noop_max: u16) -> Self {
+		BatchEnv {
+			envs: self
+				.envs
+				.into_iter()
+				.map(|env| env.with_noop_max(noop_max))
+				.collect(),
+			..self
+		}
+	}
+
+	/// Steps the matches on the rayon thread pool.
+	#[cfg(feature = "parallel")]
+	pub fn with_parallel(self, parallel: bool) -> Self {
+		BatchEnv { parallel, ..self }
+	}
+
+	/// Resets every match, match `i` with `seed + i`.
+	pub fn reset(&mut self, seed: u64) -> &[f32] {
+		for (i, (env, obs)) in self
+			.envs
+			.iter_mut()
+			.zip(self.obs.chunks_exact_mut(MATCH_OBS_LEN))
+			.enumerate()
+		{
+			env.reset(seed.wrapping_add(i as u64));
+			write_obs(env, obs);
+		}
+
+		self.rewards.fill(0.0);
+		self.dones.fill(false);
+		self.new_matches.fill(false);
+
+		&self.obs
+	}
+
+	/// Steps every match by one frame. `actions` holds the discrete actions of both players for
+	/// each match, `[p1, p2, p1, p2, ...]`, each below [`ACTION_COUNT`](crate::env::ACTION_COUNT).
+	pub fn step(&mut self, actions: &[u8]) {
+		assert_eq!(
+			actions.len(),
+			self.envs.len() * 2,
+			"Expected two actions per match."
+		);
+
+		#[cfg(feature = "parallel")]
+		if self.parallel {
+			self.envs
+				.par_iter_mut()
+				.zip(actions.par_chunks_exact(2))
+				.zip(self.obs.par_chunks_exact_mut(MATCH_OBS_LEN))
+				.zip(self.rewards.par_chunks_exact_mut(2))
+				.zip(self.dones.par_iter_mut())
+				.zip(self.new_matches.par_iter_mut())
+				.with_min_len(64)
+				.for_each(
+					|(((((env, actions), obs), reward), done), new_match)| {
+						step_env(env, actions, obs, reward, done, new_match)
+					},
+				);
+
+			return;
+		}
+
+		self.envs
+			.iter_mut()
+			.zip(actions.chunks_exact(2))
+			.zip(self.obs.chunks_exact_mut(MATCH_OBS_LEN))
+			.zip(self.rewards.chunks_exact_mut(2))
+			.zip(self.dones.iter_mut())
+			.zip(self.new_matches.iter_mut())
+			.for_each(|(((((env, actions), obs), reward), done), new_match)| {
+				step_env(env, actions, obs, reward, done, new_match)
+			});
+	}
+
+	/// `[len, 2, OBS_LEN]` observations.
+	#[inline]
+	pub fn observations(&self) -> &[f32] {
+		&self.obs
+	}
+
+	/// `[len, 2]` rewards of the last step.
+	#[inline]
+	pub fn rewards(&self) -> &[f32] {
+		&self.rewards
+	}
+
+	/// Matches whose episode ended on the last step.
+	#[inline]
+	pub fn dones(&self) -> &[bool] {
+		&self.dones
+	}
+
+	/// Matches that were decided on the last step and restarted from the initial state.
+	#[inline]
+	pub fn new_matches(&self) -> &[bool] {
+		&self.new_matches
+	}
+
+	#[inline]
+	pub fn envs(&self) -> &[Env] {
+		&self.envs
+	}
+
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.envs.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.envs.is_empty()
+	}
+}
+
+#[inline]
+fn step_env(
+	env: &mut Env,
+	actions: &[u8],
+	obs: &mut [f32],
+	reward: &mut [f32],
+	done: &mut bool,
+	new_match: &mut bool,
+) {
+	let (step_reward, step_done, _) = env.advance(actions[0], actions[1]);
+
+	reward.copy_from_slice(&step_reward);
+	*done = step_done;
+	*new_match = step_done && env.next_round();
+
+	write_obs(env, obs);
+}
+
+#[inline]
+fn write_obs(env: &Env, obs: &mut [f32]) {
+	let (p1, p2) = obs.split_at_mut(OBS_LEN);
+	env.write_observation(true, p1);
+	env.write_observation(false, p2);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::rng::Rng;
+
+	#[test]
+	fn matches_single_env() {
+		let mut batch = BatchEnv::new(Match::new(false, false), 8).with_noop_max(20);
+		let mut single: Vec<Env> = (0..8)
+			.map(|_| Env::new(Match::new(false, false)).with_noop_max(20))
+			.collect();
+
+		batch.reset(100);
+		for (i, env) in single.iter_mut().enumerate() {
+			env.reset(100 + i as u64);
+		}
+
+		let mut rng = Rng::new(1);
+		let mut dones = 0;
+		for _ in 0..5000 {
+			let actions: Vec<u8> = (0..16).map(|_| rng.below(9) as u8).collect();
+			batch.step(&actions);
+
+			for (i, env) in single.iter_mut().enumerate() {
+				let step = env.step(actions[i * 2], actions[i * 2 + 1]);
+				assert_eq!(batch.dones()[i], step.done);
+				assert_eq!(batch.rewards()[i * 2..i * 2 + 2], step.reward);
+
+				if step.done {
+					dones += 1;
+					assert_eq!(batch.new_matches()[i], env.next_round());
+				}
+
+				let obs = &batch.observations()
+					[i * MATCH_OBS_LEN..(i + 1) * MATCH_OBS_LEN];
+				assert_eq!(obs[..OBS_LEN], env.observation(true));
+				assert_eq!(obs[OBS_LEN..], env.observation(false));
+			}
+		}
+
+		assert!(dones > 0);
+	}
+
+	#[cfg(feature = "parallel")]
+	#[test]
+	fn parallel() {
+		let mut serial = BatchEnv::new(Match::new(false, false), 300);
+		let mut parallel = serial.clone().with_parallel(true);
+		serial.reset(5);
+		parallel.reset(5);
+
+		let mut rng = Rng::new(2);
+		for _ in 0..2000 {
+			let actions: Vec<u8> = (0..600).map(|_| rng.below(9) as u8).collect();
+			serial.step(&actions);
+			parallel.step(&actions);
+
+			assert_eq!(serial.observations(), parallel.observations());
+			assert_eq!(serial.rewards(), parallel.rewards());
+			assert_eq!(serial.dones(), parallel.dones());
+		}
+	}
+}
@@ -0,0 +1,298 @@
+//! Runs matches without Godot, for regression runs and bot evaluations.
+
+use std::{
+	error::Error,
+	fs,
+	io::{BufWriter, Write},
+	path::Path,
+	process::ExitCode,
+};
+
+#[cfg(feature = "onnx")]
+use footsies_sim::{bot::PolicyBot, policy::Policy};
+use footsies_sim::{
+	bot::{Bot, BotKind, Difficulty, View},
+	input::FgInput,
+	replay::{Recorder, Replay},
+	script::InputScript,
+	simul::{Match, Result},
+};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: footsies-cli [OPTIONS] <P1> <P2>
+
+Players:
+  script:FILE      inputs from an input script
+  replay:FILE      inputs of the same player in a replay, the match starts where the replay did
+  bot:NAME         built-in bot, `idle`, `random`, `turtle`, `punisher` or `search`
+  policy:FILE      bot playing an ONNX policy, needs the `onnx` feature
+
+Options:
+  --frames N       stop after N frames instead of at the end of the match
+  --seed N         seed of random bots and policies [default: 0]
+  --difficulty D   `perfect`, `hard`, `normal` or `easy` built-in bots [default: perfect]
+  --dump FILE      write the state of every frame to FILE, one frame per line
+  --format FORMAT  `ron` or `json` dumps, taken from the extension of FILE otherwise
+  --record FILE    save a replay to FILE, as RON if it ends in `.ron`
+  -h, --help       print this help";
+
+fn main() -> ExitCode {
+	let args = match Args::parse(std::env::args().skip(1)) {
+		Ok(Some(args)) => args,
+		Ok(None) => {
+			println!("{USAGE}");
+			return ExitCode::SUCCESS;
+		}
+		Err(err) => {
+			eprintln!("footsies-cli: {err}\n\n{USAGE}");
+			return ExitCode::from(2);
+		}
+	};
+
+	match run(args) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("footsies-cli: {err}");
+			ExitCode::FAILURE
+		}
+	}
+}
+
+struct Args {
+	players: [String; 2],
+	frames: Option<u32>,
+	seed: u64,
+	difficulty: Difficulty,
+	dump: Option<String>,
+	format: Option<Format>,
+	record: Option<String>,
+}
+
+impl Args {
+	/// `None` when asked for help.
+	fn parse(
+		mut args: impl Iterator<Item = String>,
+	) -> std::result::Result<Option<Self>, String> {
+		let mut players = Vec::new();
+		let mut frames = None;
+		let mut seed = 0;
+		let mut difficulty = Difficulty::Perfect;
+		let mut dump = None;
+		let mut format = None;
+		let mut record = None;
+
+		while let Some(arg) = args.next() {
+			let mut value = || args.next().ok_or(format!("`{arg}` needs a value"));
+
+			match arg.as_str() {
+				"-h" | "--help" => return Ok(None),
+				"--frames" => {
+					frames = Some(value()?
+						.parse()
+						.map_err(|_| "bad `--frames`")?);
+				}
+				"--seed" => seed = value()?.parse().map_err(|_| "bad `--seed`")?,
+				"--difficulty" => {
+					let name = value()?;
+					difficulty = Difficulty::from_name(&name)
+						.ok_or(format!("unknown difficulty `{name}`"))?;
+				}
+				"--dump" => dump = Some(value()?),
+				"--format" => format = Some(Format::new(&value()?)?),
+				"--record" => record = Some(value()?),
+				_ if arg.starts_with('-') => {
+					return Err(format!("unknown option `{arg}`"))
+				}
+				_ => players.push(arg),
+			}
+		}
+
+		let players: [String; 2] = players
+			.try_into()
+			.map_err(|_| "expected two players".to_owned())?;
+
+		Ok(Some(Args {
+			players,
+			frames,
+			seed,
+			difficulty,
+			dump,
+			format,
+			record,
+		}))
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+	Ron,
+	Json,
+}
+
+impl Format {
+	fn new(name: &str) -> std::result::Result<Self, String> {
+		match name {
+			"ron" => Ok(Format::Ron),
+			"json" => Ok(Format::Json),
+			_ => Err(format!("unknown format `{name}`")),
+		}
+	}
+}
+
+/// Where the inputs of a player come from.
+enum Source {
+	Script(InputScript),
+	Replay(Vec<FgInput>),
+	Bot(Box<dyn Bot>),
+}
+
+impl Source {
+	/// The match starts from the first replay given.
+	fn new(
+		spec: &str,
+		p1: bool,
+		args: &Args,
+		initial: &mut Option<Match>,
+	) -> std::result::Result<Self, Box<dyn Error>> {
+		let (kind, value) = spec
+			.split_once(':')
+			.ok_or_else(|| format!("player `{spec}` is not `kind:value`"))?;
+
+		Ok(match kind {
+			"script" => Source::Script(fs::read_to_string(value)?.parse()?),
+			"replay" => {
+				let replay = read_replay(Path::new(value))?;
+				let side = usize::from(!p1);
+				initial.get_or_insert_with(|| replay.initial().clone());
+
+				Source::Replay(
+					replay.inputs().iter().map(|inputs| inputs[side]).collect(),
+				)
+			}
+			"bot" => {
+				let kind = BotKind::from_name(value)
+					.ok_or_else(|| format!("unknown bot `{value}`"))?;
+				let seed = args.seed.wrapping_add(u64::from(!p1));
+				Source::Bot(kind.bot_at(args.difficulty, seed))
+			}
+			#[cfg(feature = "onnx")]
+			"policy" => {
+				let seed = args.seed.wrapping_add(u64::from(!p1));
+				Source::Bot(Box::new(PolicyBot::new(Policy::load(value)?, seed)))
+			}
+			#[cfg(not(feature = "onnx"))]
+			"policy" => return Err("built without the `onnx` feature".into()),
+			_ => return Err(format!("unknown player kind `{kind}`").into()),
+		})
+	}
+
+	#[inline]
+	const fn is_bot(&self) -> bool {
+		matches!(self, Source::Bot(_))
+	}
+
+	/// `None` once a replay runs out.
+	fn input(&mut self, frame: u32, sim: &Match, p1: bool) -> Option<FgInput> {
+		match self {
+			Source::Script(script) => Some(script.input(frame)),
+			Source::Replay(inputs) => inputs.get(frame as usize).copied(),
+			Source::Bot(bot) => Some(bot.input(View::new(sim, p1))),
+		}
+	}
+}
+
+fn read_replay(path: &Path) -> std::result::Result<Replay, Box<dyn Error>> {
+	Ok(match path.extension().is_some_and(|ext| ext == "ron") {
+		true => Replay::from_ron(&fs::read_to_string(path)?)?,
+		false => Replay::from_bin(&fs::read(path)?)?,
+	})
+}
+
+#[derive(Serialize)]
+struct Frame<'a> {
+	frame: u32,
+	inputs: [FgInput; 2],
+	result: Result,
+	state: &'a Match,
+}
+
+fn run(args: Args) -> std::result::Result<(), Box<dyn Error>> {
+	let mut initial = None;
+	let [p1, p2] = &args.players;
+	let mut sources = [
+		Source::new(p1, true, &args, &mut initial)?,
+		Source::new(p2, false, &args, &mut initial)?,
+	];
+	let mut sim =
+		initial.unwrap_or_else(|| Match::new(sources[0].is_bot(), sources[1].is_bot()));
+
+	let mut dump = match &args.dump {
+		Some(path) => {
+			let format = match args.format {
+				Some(format) => format,
+				None if path.ends_with(".ron") => Format::Ron,
+				None => Format::Json,
+			};
+			Some((BufWriter::new(fs::File::create(path)?), format))
+		}
+		None => None,
+	};
+
+	let mut recorder = Recorder::new(&sim);
+	let mut frame = 0;
+	let mut round = 0;
+	while args.frames.is_none_or(|frames| frame < frames) {
+		let (Some(input1), Some(input2)) = (
+			sources[0].input(frame, &sim, true),
+			sources[1].input(frame, &sim, false),
+		) else {
+			break;
+		};
+
+		let result = recorder.step(&mut sim, input1, input2);
+
+		if let Some((out, format)) = &mut dump {
+			let line = Frame {
+				frame,
+				inputs: [input1, input2],
+				result,
+				state: &sim,
+			};
+			match format {
+				Format::Ron => out.write_all(ron::to_string(&line)?.as_bytes())?,
+				Format::Json => serde_json::to_writer(&mut *out, &line)?,
+			}
+			out.write_all(b"\n")?;
+		}
+
+		frame += 1;
+		if !matches!(result, Result::Continue | Result::Pause) {
+			round += 1;
+			println!("round {round}: {result:?} on frame {frame}");
+
+			if !sim.continues() {
+				break;
+			}
+		}
+	}
+
+	if let Some((mut out, _)) = dump {
+		out.flush()?;
+	}
+	if let Some(path) = &args.record {
+		let replay = recorder.finish(&sim);
+		match path.ends_with(".ron") {
+			true => fs::write(path, replay.to_ron())?,
+			false => fs::write(path, replay.to_bin())?,
+		}
+	}
+
+	println!(
+		"final: {} - {} after {round} rounds, {frame} frames",
+		sim.p1_wins(),
+		sim.p2_wins()
+	);
+
+	Ok(())
+}
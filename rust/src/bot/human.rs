@@ -0,0 +1,224 @@
+use super::{Bot, View};
+use crate::{input::FgInput, rng::Rng, simul::Match};
+
+/// Frames simulated per second.
+const FPS: u8 = 60;
+
+/// Plays another bot like a human would: late, at a limited pace and now and then wrong.
+#[derive(Debug)]
+pub struct HumanBot {
+	bot: Box<dyn Bot>,
+	config: HumanConfig,
+	rng: Rng,
+	/// Last `delay + jitter + 1` states of the match, overwritten in turn.
+	states: Vec<Match>,
+	/// Index of the newest state.
+	newest: usize,
+	/// Age of the state the bot sees, `delay` plus jitter.
+	lag: u8,
+	/// Frames before the next action.
+	cooldown: u8,
+	/// Last input sent.
+	input: FgInput,
+	/// Last direction walked in.
+	direction: i8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumanConfig {
+	/// Frames between a state and the bot seeing it.
+	pub delay: u8,
+	/// Most frames randomly added to `delay`, drawn again after every action.
+	pub jitter: u8,
+	/// Most actions per second, `0` for no limit. Pressing a button and walking in another
+	/// direction are actions, a dash is a single one.
+	pub actions_per_sec: u8,
+	/// Chance of an action being swapped for a random one.
+	pub mistakes: f32,
+}
+
+impl HumanConfig {
+	/// Plays exactly like the bot it wraps.
+	pub const PERFECT: Self = HumanConfig {
+		delay: 0,
+		jitter: 0,
+		actions_per_sec: 0,
+		mistakes: 0.0,
+	};
+	pub const HARD: Self = HumanConfig {
+		delay: 12,
+		jitter: 4,
+		actions_per_sec: 12,
+		mistakes: 0.02,
+	};
+	pub const NORMAL: Self = HumanConfig {
+		delay: 16,
+		jitter: 6,
+		actions_per_sec: 8,
+		mistakes: 0.05,
+	};
+	pub const EASY: Self = HumanConfig {
+		delay: 22,
+		jitter: 10,
+		actions_per_sec: 5,
+		mistakes: 0.15,
+	};
+
+	/// Oldest state the bot can see.
+	#[inline]
+	const fn max_lag(self) -> u8 {
+		self.delay.saturating_add(self.jitter)
+	}
+
+	/// Fewest frames between two actions.
+	#[inline]
+	const fn action_gap(self) -> u8 {
+		match self.actions_per_sec {
+			0 => 0,
+			per_sec => FPS.div_ceil(per_sec),
+		}
+	}
+}
+
+impl HumanBot {
+	pub fn new(bot: Box<dyn Bot>, config: HumanConfig, seed: u64) -> Self {
+		HumanBot {
+			bot,
+			config,
+			rng: Rng::new(seed),
+			states: Vec::with_capacity(config.max_lag() as usize + 1),
+			newest: 0,
+			lag: config.delay,
+			cooldown: 0,
+			input: FgInput::default(),
+			direction: 0,
+		}
+	}
+
+	#[inline]
+	pub const fn config(&self) -> &HumanConfig {
+		&self.config
+	}
+}
+
+impl Bot for HumanBot {
+	fn input(&mut self, view: View<'_>) -> FgInput {
+		let wanted = match self.config.max_lag() {
+			// Sees the match as it is, no need to keep it.
+			0 => self.bot.input(view),
+			max_lag => {
+				let kept = max_lag as usize + 1;
+				if self.states.len() < kept {
+					self.newest = self.states.len();
+					self.states.push(view.sim().clone());
+				} else {
+					self.newest = (self.newest + 1) % kept;
+					self.states[self.newest].clone_from(view.sim());
+				}
+
+				// As old as the lag, or the oldest state kept early on.
+				let len = self.states.len();
+				let age = (self.lag as usize).min(len - 1);
+				let seen = &self.states[(self.newest + len - age) % len];
+				self.bot.input(View::new(seen, view.is_p1()))
+			}
+		};
+		self.cooldown = self.cooldown.saturating_sub(1);
+
+		let turns = wanted.movement != 0 && wanted.movement != self.direction;
+		let mut input = wanted;
+		if turns || wanted.attack_press || wanted.special_press {
+			match self.cooldown {
+				// Too soon, keeps walking the way it did and lets go of the buttons.
+				1.. => {
+					let movement = match wanted.movement {
+						0 => 0,
+						_ => self.input.movement,
+					};
+					input = FgInput::new(movement, false, false);
+				}
+				0 => {
+					if self.rng.chance(self.config.mistakes) {
+						input = FgInput::new(
+							self.rng.below(3) as i8 - 1,
+							self.rng.chance(0.5),
+							self.rng.chance(0.25),
+						);
+					}
+
+					self.cooldown = self.config.action_gap();
+					let jitter = self.rng.below(self.config.jitter as u32 + 1);
+					self.lag = self.config.delay.saturating_add(jitter as u8);
+				}
+			}
+		}
+
+		if input.movement != 0 {
+			self.direction = input.movement;
+		}
+		self.input = input;
+
+		input
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::simul::GameState;
+
+	/// Walks forward once the round starts and mashes attack.
+	#[derive(Debug)]
+	struct Eager;
+
+	impl Bot for Eager {
+		fn input(&mut self, view: View<'_>) -> FgInput {
+			let active = view.sim().game_state() == GameState::Active;
+			FgInput::new(active as i8, true, false)
+		}
+	}
+
+	/// Inputs of `bot` as player 1 for the first `frames` frames, the opponent idles.
+	fn inputs(mut bot: HumanBot, frames: usize) -> Vec<FgInput> {
+		let mut sim = Match::new(true, true);
+
+		(0..frames)
+			.map(|_| {
+				let input = bot.input(View::new(&sim, true));
+				sim.frame_update(input, FgInput::default());
+				input
+			})
+			.collect()
+	}
+
+	fn first_step(inputs: &[FgInput]) -> Option<usize> {
+		inputs.iter().position(|input| input.movement == 1)
+	}
+
+	#[test]
+	fn delay() {
+		let config = HumanConfig {
+			delay: 15,
+			..HumanConfig::PERFECT
+		};
+
+		let perfect = inputs(HumanBot::new(Box::new(Eager), HumanConfig::PERFECT, 0), 300);
+		let late = inputs(HumanBot::new(Box::new(Eager), config, 0), 300);
+		assert_eq!(
+			first_step(&late),
+			first_step(&perfect).map(|frame| frame + 15)
+		);
+	}
+
+	#[test]
+	fn actions_per_sec() {
+		let config = HumanConfig {
+			actions_per_sec: 10,
+			..HumanConfig::PERFECT
+		};
+
+		let inputs = inputs(HumanBot::new(Box::new(Eager), config, 0), 60);
+		let presses = inputs.iter().filter(|input| input.attack_press).count();
+		assert_eq!(presses, 10);
+	}
+}
@@ -0,0 +1,294 @@
+//! Bots that play one side of a [`Match`], one frame at a time.
+//!
+//! Bots keep state of their own outside of the match, so rolling a match back does not roll its
+//! bots back. Ask a bot once per frame and treat its input like a player's: a rollback replays the
+//! recorded inputs instead of asking the bot again.
+
+mod human;
+#[cfg(feature = "onnx")]
+mod policy;
+mod punisher;
+mod random;
+mod search;
+mod turtle;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "onnx")]
+pub use self::policy::PolicyBot;
+pub use self::{
+	human::{HumanBot, HumanConfig},
+	punisher::PunisherBot,
+	random::RandomBot,
+	search::{Action, OpponentModel, SearchBot, SearchConfig},
+	turtle::TurtleBot,
+};
+use crate::{
+	analysis::AttackData,
+	cbox::CBox,
+	event::{Attack, Event},
+	input::FgInput,
+	player::Player,
+	simul::Match,
+};
+
+pub trait Bot: fmt::Debug {
+	/// Input for the next frame. Movement is relative to the bot, positive walks towards the
+	/// opponent.
+	fn input(&mut self, view: View<'_>) -> FgInput;
+}
+
+/// Read-only [`Match`] as seen by the bot playing player 1 or player 2.
+#[derive(Debug, Clone, Copy)]
+pub struct View<'a> {
+	sim: &'a Match,
+	p1: bool,
+}
+
+impl<'a> View<'a> {
+	#[inline]
+	pub const fn new(sim: &'a Match, p1: bool) -> Self {
+		View { sim, p1 }
+	}
+
+	#[inline]
+	pub const fn sim(self) -> &'a Match {
+		self.sim
+	}
+
+	#[inline]
+	pub const fn is_p1(self) -> bool {
+		self.p1
+	}
+
+	#[inline]
+	pub const fn me(self) -> &'a Player {
+		self.sim.player(self.p1)
+	}
+
+	#[inline]
+	pub const fn opponent(self) -> &'a Player {
+		self.sim.player(!self.p1)
+	}
+
+	/// Distance from the stage edge behind the bot.
+	#[inline]
+	pub fn position(self) -> i16 {
+		self.sim.player_relative_pos(self.p1)
+	}
+
+	#[inline]
+	pub fn distance(self) -> i16 {
+		self.sim.player_distance()
+	}
+
+	/// Events of the last frame.
+	#[inline]
+	pub fn events(self) -> &'a [Event] {
+		self.sim.events()
+	}
+
+	/// See [`Match::can_punish`].
+	#[inline]
+	pub fn can_punish(self, attack: Attack) -> bool {
+		self.sim.can_punish(self.p1, attack)
+	}
+
+	/// Furthest distance at which a normal of the opponent hits a standing player.
+	pub fn opponent_reach(self) -> i16 {
+		let moves = self.opponent().moves();
+
+		[Attack::NNormal, Attack::MNormal]
+			.into_iter()
+			.filter_map(|attack| AttackData::new(moves.attack(attack)))
+			.map(|data| data.max_range(CBox::base_hurtbox()))
+			.max()
+			.unwrap_or(0)
+	}
+}
+
+/// Does nothing, a training target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdleBot;
+
+impl Bot for IdleBot {
+	fn input(&mut self, _view: View<'_>) -> FgInput {
+		FgInput::default()
+	}
+}
+
+/// The built-in bots, for picking one by name or from Godot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum BotKind {
+	Idle,
+	Random,
+	Turtle,
+	Punisher,
+	Search,
+}
+
+impl BotKind {
+	pub const ALL: [BotKind; 5] = [
+		BotKind::Idle,
+		BotKind::Random,
+		BotKind::Turtle,
+		BotKind::Punisher,
+		BotKind::Search,
+	];
+
+	#[inline]
+	pub const fn name(self) -> &'static str {
+		match self {
+			BotKind::Idle => "idle",
+			BotKind::Random => "random",
+			BotKind::Turtle => "turtle",
+			BotKind::Punisher => "punisher",
+			BotKind::Search => "search",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|kind| kind.name() == name)
+	}
+
+	/// `seed` only matters to bots that make random choices.
+	pub fn bot(self, seed: u64) -> Box<dyn Bot> {
+		match self {
+			BotKind::Idle => Box::new(IdleBot),
+			BotKind::Random => Box::new(RandomBot::new(seed)),
+			BotKind::Turtle => Box::new(TurtleBot::new()),
+			BotKind::Punisher => Box::new(PunisherBot::new()),
+			BotKind::Search => Box::new(SearchBot::default()),
+		}
+	}
+
+	/// [`BotKind::bot`] played at `difficulty`.
+	pub fn bot_at(self, difficulty: Difficulty, seed: u64) -> Box<dyn Bot> {
+		difficulty.humanlike(self.bot(seed), seed)
+	}
+
+	/// [`BotKind::bot_at`] for matches played in real time, where every frame has to be quick.
+	/// Searches get the smaller budget of [`SearchConfig::realtime`].
+	pub fn realtime_bot_at(self, difficulty: Difficulty, seed: u64) -> Box<dyn Bot> {
+		match self {
+			BotKind::Search => difficulty.humanlike(
+				Box::new(SearchBot::new(SearchConfig::realtime(difficulty))),
+				seed,
+			),
+			_ => self.bot_at(difficulty, seed),
+		}
+	}
+}
+
+/// How humanlike bots play, the same for every [`BotKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum Difficulty {
+	/// Frame perfect, as the bot itself plays.
+	#[default]
+	Perfect,
+	Hard,
+	Normal,
+	Easy,
+}
+
+impl Difficulty {
+	pub const ALL: [Difficulty; 4] = [
+		Difficulty::Perfect,
+		Difficulty::Hard,
+		Difficulty::Normal,
+		Difficulty::Easy,
+	];
+
+	#[inline]
+	pub const fn name(self) -> &'static str {
+		match self {
+			Difficulty::Perfect => "perfect",
+			Difficulty::Hard => "hard",
+			Difficulty::Normal => "normal",
+			Difficulty::Easy => "easy",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL
+			.into_iter()
+			.find(|difficulty| difficulty.name() == name)
+	}
+
+	/// `bot` played at this difficulty.
+	fn humanlike(self, bot: Box<dyn Bot>, seed: u64) -> Box<dyn Bot> {
+		match self {
+			Difficulty::Perfect => bot,
+			_ => Box::new(HumanBot::new(bot, self.config(), !seed)),
+		}
+	}
+
+	#[inline]
+	pub const fn config(self) -> HumanConfig {
+		match self {
+			Difficulty::Perfect => HumanConfig::PERFECT,
+			Difficulty::Hard => HumanConfig::HARD,
+			Difficulty::Normal => HumanConfig::NORMAL,
+			Difficulty::Easy => HumanConfig::EASY,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::simul::Result;
+
+	/// Plays a match between two bots, returning the wins of both.
+	fn play(mut bot1: Box<dyn Bot>, mut bot2: Box<dyn Bot>) -> (u8, u8) {
+		let mut sim = Match::new(true, true);
+
+		for _ in 0..100_000 {
+			let input1 = bot1.input(View::new(&sim, true));
+			let input2 = bot2.input(View::new(&sim, false));
+			let res = sim.step(input1, input2);
+
+			if !matches!(res, Result::Continue | Result::Pause) && !sim.continues() {
+				break;
+			}
+		}
+
+		(sim.p1_wins(), sim.p2_wins())
+	}
+
+	#[test]
+	fn names() {
+		for kind in BotKind::ALL {
+			assert_eq!(BotKind::from_name(kind.name()), Some(kind));
+		}
+		assert_eq!(BotKind::from_name("nobody"), None);
+
+		for difficulty in Difficulty::ALL {
+			assert_eq!(Difficulty::from_name(difficulty.name()), Some(difficulty));
+		}
+	}
+
+	#[test]
+	fn matches() {
+		let (p1, p2) = play(BotKind::Punisher.bot(1), BotKind::Random.bot(2));
+		assert!(p1 > p2, "punisher {p1} - random {p2}");
+
+		let (p1, p2) = play(BotKind::Random.bot(1), BotKind::Turtle.bot(2));
+		assert!(p2 > p1, "random {p1} - turtle {p2}");
+
+		let (p1, p2) = play(BotKind::Random.bot(1), BotKind::Search.bot(2));
+		assert!(p2 > p1, "random {p1} - search {p2}");
+	}
+}
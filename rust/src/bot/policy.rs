@@ -0,0 +1,40 @@
+use super::{Bot, View};
+use crate::{
+	env::{self, OBS_LEN},
+	input::FgInput,
+	policy::Policy,
+	rng::Rng,
+};
+
+/// Plays a [`Policy`] every frame, seeing the match like an [`Env`](crate::env::Env) would and
+/// drawing its inputs the way the policy was trained to.
+#[derive(Debug, Clone)]
+pub struct PolicyBot {
+	policy: Policy,
+	rng: Rng,
+}
+
+impl PolicyBot {
+	#[inline]
+	pub const fn new(policy: Policy, seed: u64) -> Self {
+		PolicyBot {
+			policy,
+			rng: Rng::new(seed),
+		}
+	}
+
+	#[inline]
+	pub const fn policy(&self) -> &Policy {
+		&self.policy
+	}
+}
+
+impl Bot for PolicyBot {
+	/// Stands still on a frame the policy fails to run on.
+	fn input(&mut self, view: View<'_>) -> FgInput {
+		let mut obs = [0.0; OBS_LEN];
+		env::write_observation(view.sim(), view.is_p1(), &mut obs);
+
+		self.policy.sample(&obs, &mut self.rng).unwrap_or_default()
+	}
+}
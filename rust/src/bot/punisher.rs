@@ -0,0 +1,40 @@
+use super::{Bot, View};
+use crate::{event::Attack, input::FgInput};
+
+/// Keeps just out of the reach of the opponent and punishes its whiffs with the best attack that
+/// lands, according to the frame data.
+#[derive(Debug, Clone, Default)]
+pub struct PunisherBot;
+
+impl PunisherBot {
+	/// Best attacks first.
+	const PUNISHES: [Attack; 4] = [
+		Attack::NSpecial,
+		Attack::MSpecial,
+		Attack::NNormal,
+		Attack::MNormal,
+	];
+	/// Distance kept on top of the reach of the opponent.
+	const MARGIN: i16 = 20;
+
+	pub const fn new() -> Self {
+		PunisherBot
+	}
+}
+
+impl Bot for PunisherBot {
+	fn input(&mut self, view: View<'_>) -> FgInput {
+		if let Some(attack) = Self::PUNISHES.into_iter().find(|&a| view.can_punish(a)) {
+			return FgInput::attack(attack);
+		}
+
+		let spacing = view.opponent_reach() + Self::MARGIN;
+		let movement = match view.distance() {
+			d if d > spacing + Self::MARGIN => 1,
+			d if d <= spacing => -1,
+			_ => 0,
+		};
+
+		FgInput::new(movement, false, false)
+	}
+}
@@ -0,0 +1,32 @@
+use super::{Bot, View};
+use crate::{input::FgInput, rng::Rng};
+
+/// Mashes. Holds a random input and picks a new one every ten frames on average.
+#[derive(Debug, Clone)]
+pub struct RandomBot {
+	rng: Rng,
+	input: FgInput,
+}
+
+impl RandomBot {
+	pub const fn new(seed: u64) -> Self {
+		RandomBot {
+			rng: Rng::new(seed),
+			input: FgInput::new(0, false, false),
+		}
+	}
+}
+
+impl Bot for RandomBot {
+	fn input(&mut self, _view: View<'_>) -> FgInput {
+		if self.rng.chance(0.1) {
+			self.input = FgInput::new(
+				self.rng.below(3) as i8 - 1,
+				self.rng.chance(0.3),
+				self.rng.chance(0.1),
+			);
+		}
+
+		self.input
+	}
+}
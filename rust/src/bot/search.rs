@@ -0,0 +1,392 @@
+use std::time::{Duration, Instant};
+
+use super::{Bot, Difficulty, View};
+use crate::{
+	event::Attack,
+	input::FgInput,
+	player::Player,
+	simul::{GameState, Match},
+};
+
+/// Looks ahead by playing clones of the match, trying every action of its own against every action
+/// of the opponent for a few actions in a row. Searches deeper while its budget lasts and plays
+/// the best action of the deepest search it finished.
+#[derive(Debug, Clone)]
+pub struct SearchBot {
+	config: SearchConfig,
+	/// Action being played and how many of its frames were sent.
+	current: Option<(Action, u8)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchConfig {
+	/// Frames each action is held for.
+	pub action_len: u8,
+	/// Most actions in a row of each player to look at.
+	pub max_depth: u8,
+	/// Frames played after the last action with both players letting go, so attacks started late
+	/// get to land.
+	pub rollout: u8,
+	/// Most frames simulated per search. Unlike `time_budget`, it keeps the bot deterministic.
+	pub frame_budget: u32,
+	pub time_budget: Option<Duration>,
+	pub opponent: OpponentModel,
+}
+
+impl SearchConfig {
+	/// The bot only searches when an action ends, once every `action_len` frames. A simulated frame
+	/// costs a quarter to half a microsecond in a release build, so a search of the whole budget
+	/// takes 10 to 20 ms: fine headless, too long for a frame of a real time match. See
+	/// [`SearchConfig::realtime`].
+	pub const DEFAULT: Self = SearchConfig {
+		action_len: 6,
+		max_depth: 3,
+		rollout: 24,
+		frame_budget: 40_000,
+		time_budget: None,
+		opponent: OpponentModel::Minimax,
+	};
+
+	/// [`SearchConfig::DEFAULT`] with a budget a frame of a real time match can spare: at most about
+	/// 2 ms of a release build at [`Difficulty::Perfect`], less at lower difficulties. It rarely
+	/// gets past a depth of 2, so it plays weaker.
+	pub const fn realtime(difficulty: Difficulty) -> Self {
+		let frame_budget = match difficulty {
+			Difficulty::Perfect => 16_000,
+			Difficulty::Hard => 12_000,
+			Difficulty::Normal => 8_000,
+			Difficulty::Easy => 4_000,
+		};
+
+		SearchConfig {
+			frame_budget,
+			..Self::DEFAULT
+		}
+	}
+}
+
+impl Default for SearchConfig {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// What the opponent is expected to answer an action with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpponentModel {
+	/// Its best answer, as if it saw the action coming.
+	Minimax,
+	/// Any answer, each as likely.
+	Expectimax,
+}
+
+/// The actions searched over, each held for [`SearchConfig::action_len`] frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	Neutral,
+	Forward,
+	Back,
+	ForwardDash,
+	BackDash,
+	Attack(Attack),
+}
+
+impl Action {
+	pub const ALL: [Action; 9] = [
+		Action::Neutral,
+		Action::Forward,
+		Action::Back,
+		Action::ForwardDash,
+		Action::BackDash,
+		Action::Attack(Attack::NNormal),
+		Action::Attack(Attack::MNormal),
+		Action::Attack(Attack::NSpecial),
+		Action::Attack(Attack::MSpecial),
+	];
+
+	/// Input on `frame` of the action. Dashes tap twice from neutral, attacks press once.
+	pub const fn input(self, frame: u8) -> FgInput {
+		let movement = match self {
+			Action::Neutral | Action::Attack(_) => 0,
+			Action::Forward => 1,
+			Action::Back => -1,
+			Action::ForwardDash | Action::BackDash if frame == 0 || frame == 2 => 0,
+			Action::ForwardDash => 1,
+			Action::BackDash => -1,
+		};
+
+		match self {
+			Action::Attack(attack) if frame == 0 => FgInput::attack(attack),
+			_ => FgInput::new(movement, false, false),
+		}
+	}
+
+	/// Specials need a full meter, anything else can at least be buffered.
+	fn available(self, player: &Player) -> bool {
+		match self {
+			Action::Attack(attack) => !attack.is_special() || player.meter == 1000,
+			_ => true,
+		}
+	}
+}
+
+/// Score of a won round. Everything else scores far less.
+const WIN: f32 = 10_000.0;
+
+struct Budget {
+	frames: u32,
+	limit: u32,
+	deadline: Option<Instant>,
+}
+
+impl Budget {
+	/// `None` once the budget is spent.
+	fn spend(&mut self, frames: u32) -> Option<()> {
+		self.frames += frames;
+
+		let timed_out = self
+			.deadline
+			.is_some_and(|deadline| Instant::now() >= deadline);
+		(self.frames <= self.limit && !timed_out).then_some(())
+	}
+}
+
+impl SearchBot {
+	pub const fn new(config: SearchConfig) -> Self {
+		SearchBot {
+			config,
+			current: None,
+		}
+	}
+
+	#[inline]
+	pub const fn config(&self) -> &SearchConfig {
+		&self.config
+	}
+
+	/// Best action for `p1` or player 2, searching deeper until the budget runs out.
+	pub fn search(&self, sim: &Match, p1: bool) -> Action {
+		let mut budget = Budget {
+			frames: 0,
+			limit: self.config.frame_budget,
+			deadline: self
+				.config
+				.time_budget
+				.map(|budget| Instant::now() + budget),
+		};
+		let mut best = self.current.map_or(Action::Neutral, |(action, _)| action);
+
+		for depth in 1..=self.config.max_depth.max(1) {
+			match self.best_action(sim, p1, depth, best, &mut budget) {
+				Some((action, _)) => best = action,
+				None => break,
+			}
+		}
+
+		best
+	}
+
+	/// Best action and its value, trying `first` first so it prunes the rest.
+	fn best_action(
+		&self,
+		sim: &Match,
+		p1: bool,
+		depth: u8,
+		first: Action,
+		budget: &mut Budget,
+	) -> Option<(Action, f32)> {
+		let me = sim.player(p1);
+		let actions = [first]
+			.into_iter()
+			.chain(Action::ALL.into_iter().filter(|&action| action != first))
+			.filter(|action| action.available(me));
+
+		let mut best = (first, f32::NEG_INFINITY);
+		for action in actions {
+			let value = self.action_value(sim, p1, action, depth, best.1, budget)?;
+			if value > best.1 {
+				best = (action, value);
+			}
+		}
+
+		Some(best)
+	}
+
+	/// Value of `action` against the answers of the opponent. With [`OpponentModel::Minimax`] it
+	/// stops at the first answer worth no more than `floor`, as the action will not be picked.
+	fn action_value(
+		&self,
+		sim: &Match,
+		p1: bool,
+		action: Action,
+		depth: u8,
+		floor: f32,
+		budget: &mut Budget,
+	) -> Option<f32> {
+		let opponent = sim.player(!p1);
+		let answers = Action::ALL
+			.into_iter()
+			.filter(|answer| answer.available(opponent));
+
+		let (mut worst, mut sum, mut count) = (f32::INFINITY, 0.0, 0.0);
+		for answer in answers {
+			let mut next = sim.clone();
+			let over = self.play(&mut next, p1, action, answer, budget)?;
+
+			let value = match over || depth <= 1 {
+				true => self.leaf_value(&mut next, p1, budget)?,
+				false => {
+					let (_, value) = self.best_action(
+						&next,
+						p1,
+						depth - 1,
+						action,
+						budget,
+					)?;
+					value
+				}
+			};
+
+			worst = worst.min(value);
+			sum += value;
+			count += 1.0;
+			if self.config.opponent == OpponentModel::Minimax && worst <= floor {
+				break;
+			}
+		}
+
+		Some(match self.config.opponent {
+			OpponentModel::Minimax => worst,
+			OpponentModel::Expectimax => sum / count,
+		})
+	}
+
+	/// Plays both actions. `Some(true)` once the round is decided.
+	fn play(
+		&self,
+		sim: &mut Match,
+		p1: bool,
+		action: Action,
+		answer: Action,
+		budget: &mut Budget,
+	) -> Option<bool> {
+		for frame in 0..self.config.action_len {
+			let (mine, theirs) = (action.input(frame), answer.input(frame));
+			match p1 {
+				true => sim.frame_update(mine, theirs),
+				false => sim.frame_update(theirs, mine),
+			};
+
+			if decided(sim) {
+				budget.spend(frame as u32 + 1)?;
+				return Some(true);
+			}
+		}
+
+		budget.spend(self.config.action_len as u32)?;
+		Some(false)
+	}
+
+	fn leaf_value(&self, sim: &mut Match, p1: bool, budget: &mut Budget) -> Option<f32> {
+		let neutral = FgInput::default();
+		let mut frames = 0;
+		while frames < self.config.rollout && !decided(sim) {
+			sim.frame_update(neutral, neutral);
+			frames += 1;
+		}
+		budget.spend(frames as u32)?;
+
+		Some(evaluate(sim, p1))
+	}
+}
+
+impl Default for SearchBot {
+	fn default() -> Self {
+		Self::new(SearchConfig::DEFAULT)
+	}
+}
+
+impl Bot for SearchBot {
+	fn input(&mut self, view: View<'_>) -> FgInput {
+		if !matches!(view.sim().game_state(), GameState::Active) {
+			self.current = None;
+			return FgInput::default();
+		}
+
+		let (action, frame) = match self.current {
+			Some((action, frame)) if frame < self.config.action_len => (action, frame),
+			_ => (self.search(view.sim(), view.is_p1()), 0),
+		};
+		self.current = Some((action, frame + 1));
+
+		action.input(frame)
+	}
+}
+
+/// Somebody is down, the rest of the round does not matter.
+#[inline]
+fn decided(sim: &Match) -> bool {
+	sim.player(true).is_dead() || sim.player(false).is_dead()
+}
+
+/// How good `sim` looks for `p1` or player 2. Knockdowns dwarf health and guard left, which come
+/// before meter and the ground that decides timeouts.
+fn evaluate(sim: &Match, p1: bool) -> f32 {
+	let (me, opponent) = (sim.player(p1), sim.player(!p1));
+	match (me.is_dead(), opponent.is_dead()) {
+		(true, true) => return 0.0,
+		(true, false) => return -WIN,
+		(false, true) => return WIN,
+		(false, false) => (),
+	}
+
+	let health = sim.health_left(p1) as f32 - sim.health_left(!p1) as f32;
+	let guard = sim.guard_left(p1) as f32 - sim.guard_left(!p1) as f32;
+	let meter = me.meter as f32 - opponent.meter as f32;
+	let ground = sim.player_relative_pos(p1) as f32 - sim.player_relative_pos(!p1) as f32;
+
+	health * 20.0 + guard * 100.0 + meter * 0.1 + ground * 0.05
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn inputs() {
+		let dash: Vec<_> = (0..4)
+			.map(|frame| Action::BackDash.input(frame).movement)
+			.collect();
+		assert_eq!(dash, [0, -1, 0, -1]);
+
+		let attack = Action::Attack(Attack::MNormal);
+		assert_eq!(attack.input(0), FgInput::new(1, true, false));
+		assert_eq!(attack.input(1), FgInput::default());
+	}
+
+	#[test]
+	fn budget() {
+		let mut sim = Match::new(true, true);
+		let idle = FgInput::default();
+		while sim.game_state() != GameState::Active {
+			sim.frame_update(idle, idle);
+		}
+
+		let config = SearchConfig {
+			frame_budget: 1,
+			..SearchConfig::DEFAULT
+		};
+		// Not even a single action fits, the bot keeps doing what it did.
+		assert_eq!(SearchBot::new(config).search(&sim, true), Action::Neutral);
+	}
+
+	#[test]
+	fn realtime() {
+		let budgets: Vec<_> = Difficulty::ALL
+			.into_iter()
+			.map(|difficulty| SearchConfig::realtime(difficulty).frame_budget)
+			.collect();
+		assert!(budgets[0] < SearchConfig::DEFAULT.frame_budget);
+		assert!(budgets.is_sorted_by(|a, b| a > b), "{budgets:?}");
+	}
+}
@@ -0,0 +1,50 @@
+use super::{Bot, View};
+use crate::{event::Attack, input::FgInput};
+
+/// Holds back, which blocks with guard rules, and backdashes out of the reach of the opponent
+/// while there is room behind it. Attacks only to punish.
+#[derive(Debug, Clone, Default)]
+pub struct TurtleBot {
+	/// Inputs of a backdash still to send.
+	dash: &'static [FgInput],
+}
+
+impl TurtleBot {
+	/// A fresh back press, released and pressed again.
+	const BACKDASH: [FgInput; 4] = [
+		FgInput::new(0, false, false),
+		FgInput::new(-1, false, false),
+		FgInput::new(0, false, false),
+		FgInput::new(-1, false, false),
+	];
+	/// Room behind the bot a backdash needs.
+	const DASH_ROOM: i16 = 150;
+
+	pub const fn new() -> Self {
+		TurtleBot { dash: &[] }
+	}
+}
+
+impl Bot for TurtleBot {
+	fn input(&mut self, view: View<'_>) -> FgInput {
+		if let [input, rest @ ..] = self.dash {
+			self.dash = rest;
+			return *input;
+		}
+
+		if view.can_punish(Attack::NNormal) {
+			return FgInput::attack(Attack::NNormal);
+		}
+
+		if view.me().can_attack()
+			&& view.distance() <= view.opponent_reach()
+			&& view.position() > Self::DASH_ROOM
+		{
+			let [input, rest @ ..] = &Self::BACKDASH;
+			self.dash = rest;
+			return *input;
+		}
+
+		FgInput::new(-1, false, false)
+	}
+}
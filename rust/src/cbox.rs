@@ -1,26 +1,64 @@
 use std::ops;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+use serde::{Deserialize, Serialize};
+
+/// Box relative to its owner, for a player facing right. Horizontally it spans from `offset_x` to
+/// `offset_x + width`, vertically from `offset_y` to `offset_y + height` above the ground.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct CBox {
-	pub x: i16,
+	#[serde(default)]
+	pub offset_x: i16,
+	#[serde(default)]
+	pub offset_y: i16,
+	/// Negative widths extend behind the owner. Older frame data calls this `x`.
+	#[serde(alias = "x")]
+	pub width: i16,
+	/// Leaving it out covers a standing player, so boxes without heights always overlap
+	/// vertically, like before boxes had any.
+	#[serde(default = "CBox::default_height")]
+	pub height: i16,
 }
 
 impl CBox {
+	pub const DEFAULT_HEIGHT: i16 = 153;
+
+	/// Standing box starting at the owner, the only kind there was before boxes had heights.
+	#[inline]
+	pub const fn new(width: i16) -> Self {
+		CBox {
+			offset_x: 0,
+			offset_y: 0,
+			width,
+			height: Self::DEFAULT_HEIGHT,
+		}
+	}
+
 	#[inline]
 	pub const fn collision() -> Self {
-		CBox { x: 125 }
+		CBox::new(125)
 	}
 
 	#[inline]
 	pub const fn base_hurtbox() -> Self {
-		CBox { x: 158 }
+		CBox::new(158)
+	}
+
+	#[inline]
+	const fn default_height() -> i16 {
+		Self::DEFAULT_HEIGHT
+	}
+
+	/// A box with no area never overlaps anything.
+	#[inline]
+	pub const fn is_empty(self) -> bool {
+		self.width == 0 || self.height == 0
 	}
 
 	pub fn overlap(self, offsetx1: i16, other: CBox, offsetx2: i16) -> bool {
-		let c1x1 = offsetx1;
-		let c1x2 = self.x + offsetx1;
-		let c2x1 = offsetx2;
-		let c2x2 = other.x + offsetx2;
+		let c1x1 = self.offset_x + offsetx1;
+		let c1x2 = self.offset_x + self.width + offsetx1;
+		let c2x1 = other.offset_x + offsetx2;
+		let c2x2 = other.offset_x + other.width + offsetx2;
 		let c1xrange = (c1x1.min(c1x2), c1x1.max(c1x2));
 		let c2xrange = (c2x1.min(c2x2), c2x1.max(c2x2));
 
@@ -28,20 +66,65 @@ impl CBox {
 			return false;
 		}
 
+		let c1y1 = self.offset_y;
+		let c1y2 = self.offset_y + self.height;
+		let c2y1 = other.offset_y;
+		let c2y2 = other.offset_y + other.height;
+		let c1yrange = (c1y1.min(c1y2), c1y1.max(c1y2));
+		let c2yrange = (c2y1.min(c2y2), c2y1.max(c2y2));
+
+		if c1yrange.0 >= c2yrange.1 || c2yrange.0 >= c1yrange.1 {
+			return false;
+		}
+
 		true
 	}
 
 	#[inline]
 	pub fn overlap_amount(&self, offsetx1: i16, other: CBox, offsetx2: i16) -> i16 {
-		((self.x + offsetx1) - (other.x + offsetx2)) / 2
+		((self.offset_x + self.width + offsetx1)
+			- (other.offset_x + other.width + offsetx2))
+			/ 2
+	}
+
+	/// Where the box is on the stage when its owner stands at `offset`. Player 2 boxes are
+	/// `mirrored`, they extend to the left.
+	#[inline]
+	pub const fn world(self, offset: i16, mirrored: bool) -> WorldBox {
+		let (x1, x2) = match mirrored {
+			true => (-self.offset_x, -self.offset_x - self.width),
+			false => (self.offset_x, self.offset_x + self.width),
+		};
+		let (y1, y2) = (self.offset_y, self.offset_y + self.height);
+
+		WorldBox {
+			x: offset + if x1 < x2 { x1 } else { x2 },
+			y: if y1 < y2 { y1 } else { y2 },
+			width: (x2 - x1).abs(),
+			height: (y2 - y1).abs(),
+		}
 	}
 }
 
+/// [`CBox`] in stage coordinates, spanning `x..x + width` and `y..y + height` above the ground.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct WorldBox {
+	pub x: i16,
+	pub y: i16,
+	pub width: i16,
+	pub height: i16,
+}
+
 impl ops::Neg for CBox {
 	type Output = Self;
 
+	/// Mirrors the box horizontally.
 	fn neg(self) -> Self::Output {
-		CBox { x: -self.x }
+		CBox {
+			offset_x: -self.offset_x,
+			width: -self.width,
+			..self
+		}
 	}
 }
 
@@ -49,7 +132,7 @@ impl ops::Neg for CBox {
 macro_rules! cbox {
 	($val:expr) => {
 		if $val != 0 {
-			Some(CBox { x: $val })
+			Some(CBox::new($val))
 		} else {
 			None
 		}
@@ -62,8 +145,8 @@ mod test {
 
 	#[test]
 	fn dec_macro() {
-		assert_eq!(cbox!(10), Some(CBox { x: 10 }));
-		assert_eq!(cbox!(-10), Some(CBox { x: -10 }));
+		assert_eq!(cbox!(10), Some(CBox::new(10)));
+		assert_eq!(cbox!(-10), Some(CBox::new(-10)));
 		assert_eq!(cbox!(0), None);
 	}
 
@@ -73,6 +156,22 @@ mod test {
 		assert!(a.overlap(0, a, 0));
 		assert!(a.overlap(0, -a, 19));
 		assert!(!a.overlap(0, -a, 20));
+
+		let low = CBox {
+			height: 50,
+			..CBox::new(10)
+		};
+		let high = CBox {
+			offset_y: 100,
+			..low
+		};
+		assert!(low.overlap(0, a, 0));
+		assert!(high.overlap(0, a, 0));
+		assert!(!low.overlap(0, high, 0));
+
+		let ahead = CBox { offset_x: 15, ..a };
+		assert!(!ahead.overlap(0, a, 0));
+		assert!(ahead.overlap(0, -a, 30));
 	}
 
 	#[test]
@@ -81,4 +180,57 @@ mod test {
 		assert_eq!(a.overlap_amount(0, -a, 18), 1);
 		assert_eq!(a.overlap_amount(0, -a, 20), 0);
 	}
+
+	#[test]
+	fn world() {
+		let a = cbox!(10).unwrap();
+		let height = CBox::DEFAULT_HEIGHT;
+		assert_eq!(
+			a.world(100, false),
+			WorldBox {
+				x: 100,
+				y: 0,
+				width: 10,
+				height
+			}
+		);
+		assert_eq!(
+			a.world(100, true),
+			WorldBox {
+				x: 90,
+				y: 0,
+				width: 10,
+				height
+			}
+		);
+		assert_eq!((-a).world(100, true), a.world(100, false));
+
+		let b = CBox {
+			offset_x: 5,
+			offset_y: 20,
+			width: 10,
+			height: 30,
+		};
+		assert_eq!(
+			b.world(100, true),
+			WorldBox {
+				x: 85,
+				y: 20,
+				width: 10,
+				height: 30
+			}
+		);
+	}
+
+	#[test]
+	fn ron() {
+		let old: CBox = ron::from_str("(x: 158)").unwrap();
+		assert_eq!(old, CBox::base_hurtbox());
+
+		let new: CBox = ron::from_str("(offset_y: 80, width: 120, height: 40)").unwrap();
+		assert_eq!(
+			(new.offset_x, new.offset_y, new.width, new.height),
+			(0, 80, 120, 40)
+		);
+	}
 }
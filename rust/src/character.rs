@@ -0,0 +1,191 @@
+use std::{
+	fmt, fs, io,
+	path::Path,
+	sync::{Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::framedata::{MoveSet, MoveSetError};
+
+/// A fighter definition. Everything that differs between characters, like walk speeds, dash
+/// lengths and hurtboxes, lives in its frame data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Character {
+	pub name: String,
+	pub moves: MoveSet,
+}
+
+impl Character {
+	/// Shipped character, always registered as [`CharacterId::DEFAULT`].
+	pub const DEFAULT_RON: &str = include_str!("../data/characters/default.ron");
+
+	pub fn from_ron(from: &str) -> Result<Self, CharacterError> {
+		let character: Self = ron::from_str(from)?;
+		character.moves.validate()?;
+
+		Ok(character)
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, CharacterError> {
+		Self::from_ron(&fs::read_to_string(path)?)
+	}
+
+	pub fn to_ron(&self) -> String {
+		ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+			.expect("Could not serialize `Character`.")
+	}
+}
+
+/// Index of a registered [`Character`]. This is what `Player` stores, so characters are not
+/// copied around on clone or serialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CharacterId(pub u8);
+
+impl CharacterId {
+	pub const DEFAULT: Self = CharacterId(0);
+
+	/// Panics if no character is registered with this id.
+	#[inline]
+	pub fn get(self) -> &'static Character {
+		match self.0 {
+			0 => default_character(),
+			id => CHARACTERS[id as usize]
+				.get()
+				.unwrap_or_else(|| panic!("Character {id} is not registered.")),
+		}
+	}
+
+	pub fn is_registered(self) -> bool {
+		self.0 == 0 || CHARACTERS[self.0 as usize].get().is_some()
+	}
+
+	pub fn by_name(name: &str) -> Option<Self> {
+		(0..=u8::MAX)
+			.map(CharacterId)
+			.take_while(|id| id.is_registered())
+			.find(|id| id.get().name == name)
+	}
+}
+
+// Characters are never removed, which keeps lookups lock free and ids valid for the whole run.
+static CHARACTERS: [OnceLock<Box<Character>>; 256] = [const { OnceLock::new() }; 256];
+static NEXT_ID: Mutex<u8> = Mutex::new(1);
+
+fn default_character() -> &'static Character {
+	CHARACTERS[0].get_or_init(|| {
+		Box::new(
+			Character::from_ron(Character::DEFAULT_RON)
+				.expect("Default character is invalid."),
+		)
+	})
+}
+
+/// Makes a character selectable for new matches.
+pub fn register_character(character: Character) -> Result<CharacterId, CharacterError> {
+	character.moves.validate()?;
+
+	let mut next = NEXT_ID.lock().unwrap_or_else(|err| err.into_inner());
+	// Wraps to the default character's id once every slot is taken.
+	if *next == 0 {
+		return Err(CharacterError::RegistryFull);
+	}
+
+	let id = CharacterId(*next);
+	CHARACTERS[id.0 as usize]
+		.set(Box::new(character))
+		.expect("Character ids are never reused.");
+	*next = next.wrapping_add(1);
+
+	Ok(id)
+}
+
+#[derive(Debug)]
+pub enum CharacterError {
+	Io(io::Error),
+	Parse(ron::error::SpannedError),
+	Invalid(MoveSetError),
+	RegistryFull,
+}
+
+impl fmt::Display for CharacterError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CharacterError::Io(err) => write!(f, "could not read character: {err}"),
+			CharacterError::Parse(err) => write!(f, "could not parse character: {err}"),
+			CharacterError::Invalid(err) => write!(f, "invalid frame data: {err}"),
+			CharacterError::RegistryFull => {
+				write!(f, "too many characters are registered")
+			}
+		}
+	}
+}
+
+impl std::error::Error for CharacterError {}
+
+impl From<io::Error> for CharacterError {
+	fn from(err: io::Error) -> Self {
+		CharacterError::Io(err)
+	}
+}
+
+impl From<ron::error::SpannedError> for CharacterError {
+	fn from(err: ron::error::SpannedError) -> Self {
+		CharacterError::Parse(err)
+	}
+}
+
+impl From<MoveSetError> for CharacterError {
+	fn from(err: MoveSetError) -> Self {
+		CharacterError::Invalid(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{cbox::CBox, framedata::move_length};
+
+	#[test]
+	fn default_ron() {
+		let character = Character::from_ron(Character::DEFAULT_RON).unwrap();
+		let moves = &character.moves;
+
+		assert_eq!(move_length(&moves.idle), 24);
+		assert_eq!(move_length(&moves.nnormal), 23);
+		assert_eq!(move_length(&moves.mnormal), 22);
+		assert_eq!(move_length(&moves.nspecial), 42);
+		assert_eq!(move_length(&moves.mspecial), 55);
+		assert_eq!(move_length(&moves.fdash), 16);
+		assert_eq!(move_length(&moves.bdash), 22);
+
+		assert_eq!(moves.fwalk[0].data.speed, 6);
+		assert_eq!(moves.nnormal[2].data.hitbox, Some(CBox::new(299)));
+		assert_eq!(moves.mspecial[0].data.hurtbox, [None, None]);
+		assert_eq!(moves.normal_dead.animation_frame, "hit_0");
+
+		assert_eq!(Character::from_ron(&character.to_ron()).unwrap(), character);
+		assert_eq!(CharacterId::DEFAULT.get(), &character);
+		assert_eq!(CharacterId::by_name("Default"), Some(CharacterId::DEFAULT));
+	}
+
+	#[test]
+	fn register() {
+		let mut character = CharacterId::DEFAULT.get().clone();
+		character.name = "Register Test".to_owned();
+
+		let id = register_character(character).unwrap();
+		assert_ne!(id, CharacterId::DEFAULT);
+		assert_eq!(id.get().name, "Register Test");
+		assert_eq!(CharacterId::by_name("Register Test"), Some(id));
+
+		let mut character = CharacterId::DEFAULT.get().clone();
+		character.moves.idle.clear();
+		assert!(matches!(
+			register_character(character),
+			Err(CharacterError::Invalid(MoveSetError::Empty {
+				name: "idle"
+			}))
+		));
+	}
+}
@@ -0,0 +1,333 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timer::Timer;
+
+/// Rules of a match. Stored in the [`Match`](crate::simul::Match) itself, so it is part of every
+/// saved state and replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchConfig {
+	/// Frames per round, or [`Timer::Unlimited`] for untimed rounds.
+	pub round_time: Timer,
+	/// Round wins that end the match.
+	pub wins_needed: u8,
+	pub round_start_len: u8,
+	pub hitstop_len: u8,
+	pub round_end_len: u8,
+	pub stage_len: i16,
+	pub p1_start: i16,
+	pub p2_start: i16,
+	/// Share of the meter kept into the next round.
+	pub meter_carry_percent: u8,
+	/// Blocking rules, `None` for the classic rules where every hit kills.
+	pub guard: Option<GuardConfig>,
+	/// Health rules, `None` for the classic rules where every hit kills.
+	pub health: Option<HealthConfig>,
+}
+
+impl MatchConfig {
+	pub const DEFAULT: Self = MatchConfig {
+		round_time: Timer::Limited(3600),
+		wins_needed: 3,
+		round_start_len: 90,
+		hitstop_len: 15,
+		round_end_len: 60,
+		stage_len: 1530,
+		p1_start: 400,
+		p2_start: 1530 - 400,
+		meter_carry_percent: 50,
+		guard: None,
+		health: None,
+	};
+
+	/// Longest stage. Positions, speeds and box offsets are added up as `i16`, this leaves them
+	/// plenty of room.
+	pub const MAX_STAGE_LEN: i16 = 8192;
+	/// Fastest pushback and knockback.
+	pub const MAX_PUSH_SPEED: i16 = 1024;
+
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.round_time == Timer::Limited(0) {
+			return Err(ConfigError::Zero { name: "round_time" });
+		}
+
+		for (name, value) in [
+			("wins_needed", self.wins_needed),
+			("round_start_len", self.round_start_len),
+			("hitstop_len", self.hitstop_len),
+			("round_end_len", self.round_end_len),
+		] {
+			if value == 0 {
+				return Err(ConfigError::Zero { name });
+			}
+		}
+
+		if self.stage_len <= 0 {
+			return Err(ConfigError::Zero { name: "stage_len" });
+		}
+		if self.stage_len > Self::MAX_STAGE_LEN {
+			return Err(ConfigError::TooLarge {
+				name: "stage_len",
+				max: Self::MAX_STAGE_LEN,
+			});
+		}
+		if !(0 <= self.p1_start
+			&& self.p1_start < self.p2_start
+			&& self.p2_start <= self.stage_len)
+		{
+			return Err(ConfigError::StartPositions);
+		}
+		if self.meter_carry_percent > 100 {
+			return Err(ConfigError::MeterCarry);
+		}
+		if let Some(guard) = self.guard {
+			guard.validate()?;
+		}
+		if let Some(health) = self.health {
+			health.validate()?;
+		}
+
+		Ok(())
+	}
+
+	pub fn from_ron(from: &str) -> Result<Self, ConfigError> {
+		let config: Self = ron::from_str(from)?;
+		config.validate()?;
+
+		Ok(config)
+	}
+
+	pub fn to_ron(&self) -> String {
+		ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+			.expect("Could not serialize `MatchConfig`.")
+	}
+
+	/// Position a player starts each round at.
+	#[inline]
+	pub const fn start(&self, p1: bool) -> i16 {
+		match p1 {
+			true => self.p1_start,
+			false => self.p2_start,
+		}
+	}
+
+	/// Meter left at the start of the next round.
+	#[inline]
+	pub const fn carried_meter(&self, meter: u16) -> u16 {
+		(meter as u32 * self.meter_carry_percent as u32 / 100) as u16
+	}
+}
+
+impl Default for MatchConfig {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// Holding back while standing or walking blocks attacks. Every blocked attack takes guard points,
+/// the one that takes the last of them breaks the guard and hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardConfig {
+	/// Frames a player can not act for after blocking.
+	pub blockstun_len: u8,
+	/// Backward speed of a player in blockstun.
+	pub pushback: i16,
+	/// Guard points each player starts a round with.
+	pub guard_max: u8,
+	/// Guard points a blocked normal takes.
+	pub normal_damage: u8,
+	/// Guard points a blocked special takes.
+	pub special_damage: u8,
+}
+
+impl GuardConfig {
+	pub const DEFAULT: Self = GuardConfig {
+		blockstun_len: 15,
+		pushback: 6,
+		guard_max: 3,
+		normal_damage: 1,
+		special_damage: 3,
+	};
+
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.blockstun_len == 0 {
+			return Err(ConfigError::Zero {
+				name: "guard.blockstun_len",
+			});
+		}
+		if self.guard_max == 0 {
+			return Err(ConfigError::Zero {
+				name: "guard.guard_max",
+			});
+		}
+		if self.pushback.unsigned_abs() > MatchConfig::MAX_PUSH_SPEED as u16 {
+			return Err(ConfigError::TooLarge {
+				name: "guard.pushback",
+				max: MatchConfig::MAX_PUSH_SPEED,
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Guard points a blocked attack takes.
+	#[inline]
+	pub const fn damage(&self, special: bool) -> u8 {
+		match special {
+			true => self.special_damage,
+			false => self.normal_damage,
+		}
+	}
+}
+
+impl Default for GuardConfig {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+/// Hits take the `damage` of the frame data from the health of the defender and only knock them
+/// down once it runs out. Until then the defender is knocked back in hitstun.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+	/// Health each player starts a round with.
+	pub max_health: u16,
+	/// Frames a player that got hit can not act for.
+	pub hitstun_len: u8,
+	/// Backward speed of a player in hitstun.
+	pub knockback: i16,
+}
+
+impl HealthConfig {
+	pub const DEFAULT: Self = HealthConfig {
+		max_health: 100,
+		hitstun_len: 20,
+		knockback: 8,
+	};
+
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.max_health == 0 {
+			return Err(ConfigError::Zero {
+				name: "health.max_health",
+			});
+		}
+		if self.hitstun_len == 0 {
+			return Err(ConfigError::Zero {
+				name: "health.hitstun_len",
+			});
+		}
+		if self.knockback.unsigned_abs() > MatchConfig::MAX_PUSH_SPEED as u16 {
+			return Err(ConfigError::TooLarge {
+				name: "health.knockback",
+				max: MatchConfig::MAX_PUSH_SPEED,
+			});
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for HealthConfig {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+	Parse(ron::error::SpannedError),
+	Zero { name: &'static str },
+	/// Past its limit, or below minus it for speeds.
+	TooLarge { name: &'static str, max: i16 },
+	StartPositions,
+	MeterCarry,
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ConfigError::Parse(err) => write!(f, "could not parse match config: {err}"),
+			ConfigError::Zero { name } => {
+				write!(f, "`{name}` has to be greater than zero")
+			}
+			ConfigError::TooLarge { name, max } => {
+				write!(f, "`{name}` has to be at most {max}")
+			}
+			ConfigError::StartPositions => {
+				write!(f, "start positions have to be on the stage with player 1 on the left")
+			}
+			ConfigError::MeterCarry => write!(f, "`meter_carry_percent` is over 100"),
+		}
+	}
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<ron::error::SpannedError> for ConfigError {
+	fn from(err: ron::error::SpannedError) -> Self {
+		ConfigError::Parse(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn validate() {
+		assert!(MatchConfig::DEFAULT.validate().is_ok());
+		// Same as the old `meter * 2 / 4`.
+		assert_eq!(MatchConfig::DEFAULT.carried_meter(999), 999 * 2 / 4);
+
+		let config =
+			MatchConfig::from_ron("(wins_needed: 5, round_time: Unlimited)").unwrap();
+		assert_eq!(config.wins_needed, 5);
+		assert_eq!(config.round_time, Timer::Unlimited);
+		assert_eq!(config.stage_len, MatchConfig::DEFAULT.stage_len);
+		assert_eq!(MatchConfig::from_ron(&config.to_ron()).unwrap(), config);
+
+		assert!(matches!(
+			MatchConfig::from_ron("(hitstop_len: 0)"),
+			Err(ConfigError::Zero {
+				name: "hitstop_len"
+			})
+		));
+		assert!(matches!(
+			MatchConfig::from_ron("(p1_start: 1200)"),
+			Err(ConfigError::StartPositions)
+		));
+		assert!(matches!(
+			MatchConfig::from_ron("(stage_len: 30000, p2_start: 29000)"),
+			Err(ConfigError::TooLarge {
+				name: "stage_len",
+				..
+			})
+		));
+		assert!(matches!(
+			MatchConfig::from_ron("(health: Some((knockback: -32768)))"),
+			Err(ConfigError::TooLarge {
+				name: "health.knockback",
+				..
+			})
+		));
+
+		let config = MatchConfig::from_ron("(guard: Some((guard_max: 5)))").unwrap();
+		assert_eq!(
+			config.guard,
+			Some(GuardConfig {
+				guard_max: 5,
+				..GuardConfig::DEFAULT
+			})
+		);
+		assert!(matches!(
+			MatchConfig::from_ron("(guard: Some((blockstun_len: 0)))"),
+			Err(ConfigError::Zero {
+				name: "guard.blockstun_len"
+			})
+		));
+	}
+}
@@ -0,0 +1,133 @@
+//! Desync detection between peers running the same [`Match`].
+
+use std::{fmt, io};
+
+use serde_json::Value;
+
+use crate::simul::{Match, StateError};
+
+/// Stable hash of the whole match state, the same on every platform and build that serializes
+/// [`Match`] the same way. Peers compare it every few frames to find out they desynced.
+pub fn checksum(sim: &Match) -> u64 {
+	use bincode::{config, serde};
+
+	// Standard config is little endian with variable length integers, so the hashed bytes do not
+	// depend on the platform.
+	let mut hasher = Fnv1a::new();
+	serde::encode_into_std_write(sim, &mut hasher, config::standard())
+		.expect("Could not serialize `Match`.");
+
+	hasher.0
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` whose output may change between Rust versions.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+	const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+	const fn new() -> Self {
+		Fnv1a(Self::OFFSET)
+	}
+}
+
+impl io::Write for Fnv1a {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		for &byte in buf {
+			self.0 = (self.0 ^ byte as u64).wrapping_mul(Self::PRIME);
+		}
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// A field that has a different value in two states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+	/// Path to the field, like `player2.dash_buff.frame`.
+	pub path: String,
+	pub left: String,
+	pub right: String,
+}
+
+impl fmt::Display for Difference {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {} != {}", self.path, self.left, self.right)
+	}
+}
+
+/// Every field that differs between two states, sorted by path. Empty if they are equal.
+pub fn desync_report(left: &Match, right: &Match) -> Vec<Difference> {
+	let left = serde_json::to_value(left).expect("Could not serialize `Match`.");
+	let right = serde_json::to_value(right).expect("Could not serialize `Match`.");
+
+	let mut diffs = Vec::new();
+	diff_values(String::new(), &left, &right, &mut diffs);
+
+	diffs
+}
+
+/// [`desync_report`] of two states serialized with [`Match::serialize_bin`].
+pub fn desync_report_bin(left: &[u8], right: &[u8]) -> Result<Vec<Difference>, StateError> {
+	Ok(desync_report(
+		&Match::from_bin(left)?,
+		&Match::from_bin(right)?,
+	))
+}
+
+fn diff_values(path: String, left: &Value, right: &Value, diffs: &mut Vec<Difference>) {
+	match (left, right) {
+		// Structs, and enum variants that stayed the same.
+		(Value::Object(l), Value::Object(r)) if l.keys().eq(r.keys()) => {
+			for ((key, l), r) in l.iter().zip(r.values()) {
+				let path = match path.is_empty() {
+					true => key.clone(),
+					false => format!("{path}.{key}"),
+				};
+				diff_values(path, l, r, diffs);
+			}
+		}
+		(Value::Array(l), Value::Array(r)) if l.len() == r.len() => {
+			for (index, (l, r)) in l.iter().zip(r).enumerate() {
+				diff_values(format!("{path}[{index}]"), l, r, diffs);
+			}
+		}
+		(l, r) if l != r => diffs.push(Difference {
+			path,
+			left: l.to_string(),
+			right: r.to_string(),
+		}),
+		_ => (),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::input::FgInput;
+
+	#[test]
+	fn checksum_and_report() {
+		let mut left = Match::new(false, false);
+		let mut right = left.clone();
+		assert_eq!(checksum(&left), checksum(&right));
+		assert!(desync_report(&left, &right).is_empty());
+
+		for _ in 0..100 {
+			left.frame_update(FgInput::new(1, false, false), FgInput::default());
+			right.frame_update(FgInput::default(), FgInput::default());
+		}
+		assert_ne!(checksum(&left), checksum(&right));
+
+		let report =
+			desync_report_bin(&left.serialize_bin(), &right.serialize_bin()).unwrap();
+		let paths: Vec<_> = report.iter().map(|d| d.path.as_str()).collect();
+		assert!(paths.contains(&"player1.position"));
+		assert!(paths.iter().all(|p| p.starts_with("player1.")));
+	}
+}
@@ -0,0 +1,328 @@
+//! Reinforcement learning environment over [`Match`].
+
+use crate::{
+	input::FgInput,
+	player::{Player, PlayerState},
+	rng::Rng,
+	simul::{GameState, Match, Result},
+};
+
+/// Number of discrete actions, see [`action_input`].
+pub const ACTION_COUNT: usize = 9;
+
+/// Length of an [`Observation`].
+pub const OBS_LEN: usize = 8 + 2 * PLAYER_OBS_LEN + GameState::COUNT;
+const PLAYER_OBS_LEN: usize = OBS_STATE_COUNT + 2;
+/// One-hot slots for the player state. States of the optional rules share a slot with the closest
+/// classic state, so the layout does not depend on the rules.
+const OBS_STATE_COUNT: usize = 11;
+
+/// Normalized view of a match from one player's side. Every value is in `0.0..=1.0` and positions
+/// are mirrored for player 2, so both players can share the same policy.
+///
+/// | Index | Value |
+/// | --- | --- |
+/// | 0 | Own position, measured from own starting side |
+/// | 1 | Opponent position, measured from own starting side |
+/// | 2 | Distance between players |
+/// | 3, 4 | Own and opponent meter |
+/// | 5, 6 | Own and opponent wins |
+/// | 7.. | Own state one-hot, followed by state progress and whether the move has hit |
+/// | 20.. | Same for the opponent |
+/// | 33 | Remaining round time |
+/// | 34.. | Game state one-hot |
+pub type Observation = [f32; OBS_LEN];
+
+/// Maps a discrete action to an input. Actions are `movement * 3 + button` where movement is
+/// back, neutral, forward and button is none, attack, special. Movement is already relative to the
+/// player's facing, so the same action means the same thing for both sides. `action` has to be
+/// below [`ACTION_COUNT`].
+pub const fn action_input(action: u8) -> FgInput {
+	debug_assert!((action as usize) < ACTION_COUNT, "action out of range");
+	let movement = (action / 3) as i8 - 1;
+	let button = action % 3;
+
+	FgInput::new(movement, button == 1, button == 2)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Info {
+	pub result: Result,
+	/// Frames since the last reset.
+	pub frame: u32,
+	pub p1_wins: u8,
+	pub p2_wins: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+	pub obs: [Observation; 2],
+	pub reward: [f32; 2],
+	/// The round is over and the environment has to be reset.
+	pub done: bool,
+	pub info: Info,
+}
+
+/// One round per episode. Each step is a single frame, frames where inputs do nothing (hitstop and
+/// the round end) are still stepped and reported with [`Result::Pause`].
+#[derive(Debug, Clone)]
+pub struct Env {
+	initial: Match,
+	sim: Match,
+	rng: Rng,
+	noop_max: u16,
+	frame: u32,
+}
+
+impl Env {
+	pub fn new(initial: Match) -> Self {
+		Env {
+			sim: initial.clone(),
+			initial,
+			rng: Rng::new(0),
+			noop_max: 0,
+			frame: 0,
+		}
+	}
+
+	/// Starts each episode with a random number of idle frames, up to `noop_max`, so episodes do
+	/// not all begin from the exact same state.
+	pub fn with_noop_max(self, noop_max: u16) -> Self {
+		Env { noop_max, ..self }
+	}
+
+	/// Restarts from the initial match and skips the round start countdown.
+	pub fn reset(&mut self, seed: u64) -> [Observation; 2] {
+		self.sim = self.initial.clone();
+		self.rng = Rng::new(seed);
+		self.start_round();
+
+		[self.observation(true), self.observation(false)]
+	}
+
+	/// Moves on after a finished episode the same way the game does: the next round of the match
+	/// if it [`continues`](Match::continues), otherwise a new match with a seed drawn from the
+	/// previous one. Returns `true` if a new match was started.
+	pub fn next_round(&mut self) -> bool {
+		self.sim.new_round();
+
+		if self.sim.continues() {
+			self.start_round();
+			false
+		} else {
+			let seed = self.rng.next_u64();
+			self.reset(seed);
+			true
+		}
+	}
+
+	fn start_round(&mut self) {
+		self.frame = 0;
+
+		let idle = FgInput::default();
+		while !matches!(self.sim.game_state(), GameState::Active) {
+			self.sim.frame_update(idle, idle);
+		}
+
+		let noops = match self.noop_max {
+			0 => 0,
+			max => self.rng.below(max as u32 + 1),
+		};
+		for _ in 0..noops {
+			self.sim.frame_update(idle, idle);
+		}
+	}
+
+	/// Actions have to be below [`ACTION_COUNT`], see [`action_input`].
+	pub fn step(&mut self, action_p1: u8, action_p2: u8) -> Step {
+		let (reward, done, info) = self.advance(action_p1, action_p2);
+
+		Step {
+			obs: [self.observation(true), self.observation(false)],
+			reward,
+			done,
+			info,
+		}
+	}
+
+	/// [`Env::step`] without building the observations, for callers that write them elsewhere.
+	pub fn advance(&mut self, action_p1: u8, action_p2: u8) -> ([f32; 2], bool, Info) {
+		let result =
+			self.sim.frame_update(action_input(action_p1), action_input(action_p2));
+		self.frame += 1;
+
+		let (reward, done) = match result {
+			Result::Player1 => ([1.0, -1.0], true),
+			Result::Player2 => ([-1.0, 1.0], true),
+			Result::Draw | Result::Timeout => ([0.0, 0.0], true),
+			Result::Continue | Result::Pause => ([0.0, 0.0], false),
+		};
+
+		let info = Info {
+			result,
+			frame: self.frame,
+			p1_wins: self.sim.p1_wins(),
+			p2_wins: self.sim.p2_wins(),
+		};
+
+		(reward, done, info)
+	}
+
+	pub fn observation(&self, p1: bool) -> Observation {
+		let mut obs = [0.0; OBS_LEN];
+		self.write_observation(p1, &mut obs);
+
+		obs
+	}
+
+	/// See [`write_observation`].
+	#[inline]
+	pub fn write_observation(&self, p1: bool, out: &mut [f32]) {
+		write_observation(&self.sim, p1, out);
+	}
+
+	#[inline]
+	pub fn sim(&self) -> &Match {
+		&self.sim
+	}
+
+	#[inline]
+	pub fn frame(&self) -> u32 {
+		self.frame
+	}
+}
+
+impl Default for Env {
+	fn default() -> Self {
+		Self::new(Match::new(false, false))
+	}
+}
+
+/// Writes the [`Observation`] of a player of `sim` into the first [`OBS_LEN`] values of `out`.
+pub fn write_observation(sim: &Match, p1: bool, out: &mut [f32]) {
+	let out = &mut out[..OBS_LEN];
+	let config = sim.config();
+	let stage_len = config.stage_len as f32;
+	let wins_needed = config.wins_needed as f32;
+	let own = sim.player(p1);
+	let opp = sim.player(!p1);
+
+	out.fill(0.0);
+	out[0] = sim.player_relative_pos(p1) as f32 / stage_len;
+	out[1] = 1.0 - sim.player_relative_pos(!p1) as f32 / stage_len;
+	out[2] = sim.player_distance() as f32 / stage_len;
+	out[3] = own.meter as f32 / 1000.0;
+	out[4] = opp.meter as f32 / 1000.0;
+	out[5] = (own.wins as f32 / wins_needed).min(1.0);
+	out[6] = (opp.wins as f32 / wins_needed).min(1.0);
+
+	let (own_out, rest) = out[7..].split_at_mut(PLAYER_OBS_LEN);
+	let (opp_out, rest) = rest.split_at_mut(PLAYER_OBS_LEN);
+	write_player(own, own_out);
+	write_player(opp, opp_out);
+
+	// Untimed rounds always report zero.
+	rest[0] = match config.round_time.seconds() {
+		0 => 0.0,
+		round_secs => sim.timer_sec() as f32 / round_secs as f32,
+	};
+	rest[1 + sim.game_state().index()] = 1.0;
+}
+
+fn write_player(player: &Player, out: &mut [f32]) {
+	let state = player.state();
+
+	let slot = match state {
+		PlayerState::Block(_, _) => PlayerState::BWalk(0).index(),
+		PlayerState::Hitstun(_, _) => PlayerState::NormalDead(false).index(),
+		state => state.index(),
+	};
+
+	out[slot] = 1.0;
+	out[OBS_STATE_COUNT] = (player.state_len() as f32 / player.state_length() as f32).min(1.0);
+	out[OBS_STATE_COUNT + 1] = player.get_hit() as u8 as f32;
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::rng::Rng;
+
+	#[test]
+	fn actions() {
+		assert_eq!(action_input(0), FgInput::new(-1, false, false));
+		assert_eq!(action_input(4), FgInput::new(0, true, false));
+		assert_eq!(action_input(8), FgInput::new(1, false, true));
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "action out of range")]
+	fn invalid_action() {
+		action_input(ACTION_COUNT as u8);
+	}
+
+	#[test]
+	fn mirrored_observation() {
+		let mut env = Env::default();
+		let [p1, p2] = env.reset(0);
+
+		assert_eq!(p1, p2);
+		assert_eq!(p1[GameState::Active.index() + 34], 1.0);
+
+		let step = env.step(6, 4);
+		assert_ne!(step.obs[0], step.obs[1]);
+		assert!(step.obs[0][0] > step.obs[1][0]);
+		assert_eq!(step.obs[0][1], 1.0 - step.obs[1][0]);
+	}
+
+	#[test]
+	fn episode() {
+		let mut env = Env::default().with_noop_max(30);
+		let mut rng = Rng::new(3);
+
+		for seed in 0..4 {
+			env.reset(seed);
+
+			let step = loop {
+				let step = env.step(rng.below(9) as u8, rng.below(9) as u8);
+				assert!(step.obs.iter().flatten().all(|v| (0.0..=1.0).contains(v)));
+
+				if step.done {
+					break step;
+				}
+			};
+
+			assert_eq!(step.reward[0], -step.reward[1]);
+			assert!(matches!(
+				step.info.result,
+				Result::Player1 | Result::Player2 | Result::Draw
+			));
+		}
+	}
+
+	#[test]
+	fn next_round() {
+		let mut env = Env::default();
+		env.reset(0);
+
+		let mut rounds = 0;
+		let new_match = loop {
+			// Walk forward and attack until somebody wins.
+			let step = env.step(7, 1);
+			if step.done {
+				rounds += 1;
+
+				let (p1_wins, p2_wins) = (step.info.p1_wins, step.info.p2_wins);
+				if env.next_round() {
+					break (p1_wins, p2_wins);
+				}
+				assert_eq!(env.sim().game_state(), GameState::Active);
+			}
+		};
+
+		assert!(new_match.0 >= 3 || new_match.1 >= 3);
+		assert!(rounds >= 3);
+		assert_eq!((env.sim().p1_wins(), env.sim().p2_wins()), (0, 0));
+	}
+}
@@ -0,0 +1,245 @@
+//! What happened during a frame, as reported by [`Match::events`](crate::simul::Match::events).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	framedata::move_data,
+	player::{Player, PlayerState},
+	simul::Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum PlayerId {
+	P1,
+	P2,
+}
+
+impl PlayerId {
+	#[inline]
+	pub const fn new(p1: bool) -> Self {
+		match p1 {
+			true => PlayerId::P1,
+			false => PlayerId::P2,
+		}
+	}
+
+	#[inline]
+	pub const fn is_p1(self) -> bool {
+		matches!(self, PlayerId::P1)
+	}
+
+	#[inline]
+	pub const fn opponent(self) -> Self {
+		match self {
+			PlayerId::P1 => PlayerId::P2,
+			PlayerId::P2 => PlayerId::P1,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum Attack {
+	NNormal,
+	MNormal,
+	NSpecial,
+	MSpecial,
+}
+
+impl Attack {
+	/// Attack the player is performing in `state`, if any.
+	#[inline]
+	pub const fn from_state(state: PlayerState) -> Option<Self> {
+		match state {
+			PlayerState::NNormal(_, _) => Some(Attack::NNormal),
+			PlayerState::MNormal(_, _) => Some(Attack::MNormal),
+			PlayerState::NSpecial(_, _) => Some(Attack::NSpecial),
+			PlayerState::MSpecial(_, _) => Some(Attack::MSpecial),
+			_ => None,
+		}
+	}
+
+	#[inline]
+	pub const fn is_special(self) -> bool {
+		matches!(self, Attack::NSpecial | Attack::MSpecial)
+	}
+}
+
+/// One thing that happened during a [`Match::frame_update`](crate::simul::Match::frame_update).
+/// Events are only produced by the frame they happen on, hitstop and round end frames repeat none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+	/// First frame of an attack.
+	MoveStarted {
+		player: PlayerId,
+		attack: Attack,
+	},
+	/// First frame the hitbox of an attack is out.
+	HitboxActive {
+		player: PlayerId,
+		attack: Attack,
+	},
+	/// The hitbox of an attack went away without hitting.
+	Whiff {
+		player: PlayerId,
+		attack: Attack,
+	},
+	Hit {
+		attacker: PlayerId,
+		defender: PlayerId,
+		attack: Attack,
+	},
+	/// Blocked with [`GuardConfig`](crate::config::GuardConfig) rules.
+	Block {
+		attacker: PlayerId,
+		defender: PlayerId,
+		attack: Attack,
+	},
+	/// `player` blocked without enough guard points left. Comes right before the [`Event::Hit`] it
+	/// lets through.
+	GuardBreak {
+		player: PlayerId,
+	},
+	/// Both players hit each other on the same frame. Comes after the two [`Event::Hit`]s.
+	Trade {
+		p1_attack: Attack,
+		p2_attack: Attack,
+	},
+	DashStarted {
+		player: PlayerId,
+		forward: bool,
+	},
+	/// The meter just filled up.
+	MeterFull {
+		player: PlayerId,
+	},
+	/// The round is over with a [`Result`] other than [`Result::Continue`] and [`Result::Pause`].
+	RoundEnded {
+		result: Result,
+	},
+	/// The timer ran out. Comes right before the [`Event::RoundEnded`] it causes.
+	Timeout,
+}
+
+impl Event {
+	#[inline]
+	pub const fn kind(&self) -> EventKind {
+		match self {
+			Event::MoveStarted { .. } => EventKind::MoveStarted,
+			Event::HitboxActive { .. } => EventKind::HitboxActive,
+			Event::Whiff { .. } => EventKind::Whiff,
+			Event::Hit { .. } => EventKind::Hit,
+			Event::Block { .. } => EventKind::Block,
+			Event::GuardBreak { .. } => EventKind::GuardBreak,
+			Event::Trade { .. } => EventKind::Trade,
+			Event::DashStarted { .. } => EventKind::DashStarted,
+			Event::MeterFull { .. } => EventKind::MeterFull,
+			Event::RoundEnded { .. } => EventKind::RoundEnded,
+			Event::Timeout => EventKind::Timeout,
+		}
+	}
+
+	/// Player the event is about, the attacker for [`Event::Hit`] and [`Event::Block`].
+	#[inline]
+	pub const fn player(&self) -> Option<PlayerId> {
+		match *self {
+			Event::MoveStarted { player, .. }
+			| Event::HitboxActive { player, .. }
+			| Event::Whiff { player, .. }
+			| Event::DashStarted { player, .. }
+			| Event::MeterFull { player }
+			| Event::GuardBreak { player } => Some(player),
+			Event::Hit { attacker, .. } | Event::Block { attacker, .. } => {
+				Some(attacker)
+			}
+			Event::Trade { .. } | Event::RoundEnded { .. } | Event::Timeout => None,
+		}
+	}
+
+	/// Attack of `player`, the attack of player 1 for [`Event::Trade`].
+	#[inline]
+	pub const fn attack(&self) -> Option<Attack> {
+		match *self {
+			Event::MoveStarted { attack, .. }
+			| Event::HitboxActive { attack, .. }
+			| Event::Whiff { attack, .. }
+			| Event::Hit { attack, .. }
+			| Event::Block { attack, .. }
+			| Event::Trade {
+				p1_attack: attack, ..
+			} => Some(attack),
+			_ => None,
+		}
+	}
+}
+
+/// [`Event`] without its data, for bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum EventKind {
+	MoveStarted,
+	HitboxActive,
+	Whiff,
+	Hit,
+	Block,
+	GuardBreak,
+	Trade,
+	DashStarted,
+	MeterFull,
+	RoundEnded,
+	Timeout,
+}
+
+/// Events of the move `player` is in on the frame it just stepped to.
+pub(crate) fn push_move_events(events: &mut Vec<Event>, player: &Player, id: PlayerId) {
+	let state = player.state();
+
+	match state {
+		PlayerState::FDash(0) => events.push(Event::DashStarted {
+			player: id,
+			forward: true,
+		}),
+		PlayerState::BDash(0) => events.push(Event::DashStarted {
+			player: id,
+			forward: false,
+		}),
+		_ => (),
+	}
+
+	let Some(attack) = Attack::from_state(state) else {
+		return;
+	};
+	let frame = player.state_len() as u8;
+	if frame == 0 {
+		events.push(Event::MoveStarted { player: id, attack });
+	}
+
+	let data = match attack {
+		Attack::NNormal => &player.moves().nnormal,
+		Attack::MNormal => &player.moves().mnormal,
+		Attack::NSpecial => &player.moves().nspecial,
+		Attack::MSpecial => &player.moves().mspecial,
+	};
+	let has_hitbox = |frame| move_data(frame, data).is_some_and(|d| d.data.hitbox.is_some());
+	let active = has_hitbox(frame);
+	let was_active = frame.checked_sub(1).is_some_and(has_hitbox);
+
+	if active && !was_active {
+		events.push(Event::HitboxActive { player: id, attack });
+	} else if !active && was_active && !player.get_hit() {
+		events.push(Event::Whiff { player: id, attack });
+	}
+}
@@ -1,629 +1,170 @@
-use crate::cbox;
-use crate::cbox::CBox;
+use std::fmt;
 
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::{cbox::CBox, event::Attack};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FrameData {
 	pub speed: i16,
 	pub meter: u16,
+	/// Taken from the health of whoever the hitbox of this frame hits, with health rules.
+	pub damage: u16,
 	pub collision: CBox,
-	// If arrayvec or tinyvec crates had const ways to do this, I would used them.
 	pub hitbox: Option<CBox>,
 	pub hurtbox: [Option<CBox>; 2],
 }
 
-impl FrameData {
-	const fn default() -> Self {
+impl Default for FrameData {
+	fn default() -> Self {
 		Self {
 			speed: 0,
 			meter: 0,
+			damage: 0,
 			collision: CBox::collision(),
 			hitbox: None,
 			hurtbox: [Some(CBox::base_hurtbox()), None],
 		}
 	}
-
-	const fn fwalk() -> Self {
-		Self {
-			speed: 6,
-			meter: 3,
-			..Self::default()
-		}
-	}
-
-	const fn bwalk() -> Self {
-		Self {
-			speed: -5,
-			..Self::default()
-		}
-	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MoveData {
+	#[serde(default)]
 	pub data: FrameData,
-	pub animation_frame: &'static str,
+	pub animation_frame: String,
 	pub duration: u8,
 }
 
-pub const IDLE_DATA: [MoveData; 5] = [
-	MoveData {
-		data: FrameData::default(),
-		animation_frame: "idle_0",
-		duration: 6,
-	},
-	MoveData {
-		data: FrameData::default(),
-		animation_frame: "idle_1",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData::default(),
-		animation_frame: "idle_2",
-		duration: 6,
-	},
-	MoveData {
-		data: FrameData::default(),
-		animation_frame: "idle_3",
-		duration: 6,
-	},
-	MoveData {
-		data: FrameData::default(),
-		animation_frame: "idle_4",
-		duration: 3,
-	},
-];
+/// Frame data of every state a player can be in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveSet {
+	pub idle: Vec<MoveData>,
+	pub fwalk: Vec<MoveData>,
+	pub bwalk: Vec<MoveData>,
+	pub fdash: Vec<MoveData>,
+	pub bdash: Vec<MoveData>,
+	pub nnormal: Vec<MoveData>,
+	pub mnormal: Vec<MoveData>,
+	pub nspecial: Vec<MoveData>,
+	pub mspecial: Vec<MoveData>,
+	pub normal_dead: MoveData,
+	pub special_dead: MoveData,
+	/// Blockstun, only reachable with [`GuardConfig`](crate::config::GuardConfig) rules. Its speed
+	/// is replaced by the pushback of the rules.
+	#[serde(default = "MoveSet::default_block")]
+	pub block: MoveData,
+	/// Hitstun, only reachable with [`HealthConfig`](crate::config::HealthConfig) rules. Its speed
+	/// is replaced by the knockback of the rules.
+	#[serde(default = "MoveSet::default_hitstun")]
+	pub hitstun: MoveData,
+}
+
+impl MoveSet {
+	fn default_block() -> MoveData {
+		MoveData {
+			data: FrameData::default(),
+			animation_frame: "hblock_0".to_owned(),
+			duration: 1,
+		}
+	}
 
-pub const FWALK_DATA: [MoveData; 6] = [
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_0",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_1",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_2",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_3",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_4",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::fwalk()
-		},
-		animation_frame: "fwalk_5",
-		duration: 4,
-	},
-];
+	fn default_hitstun() -> MoveData {
+		MoveData {
+			data: FrameData::default(),
+			animation_frame: "hit_1".to_owned(),
+			duration: 1,
+		}
+	}
 
-pub const BWALK_DATA: [MoveData; 6] = [
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_0",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_1",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_2",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_3",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_4",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::bwalk()
-		},
-		animation_frame: "bwalk_5",
-		duration: 4,
-	},
-];
+	pub fn validate(&self) -> Result<(), MoveSetError> {
+		for (name, data) in self.moves() {
+			if data.is_empty() {
+				return Err(MoveSetError::Empty { name });
+			}
+
+			for (index, frame) in data.iter().enumerate() {
+				if frame.duration == 0 {
+					return Err(MoveSetError::ZeroDuration { name, index });
+				}
+
+				let mut boxes =
+					frame.data.hurtbox.iter().chain([&frame.data.hitbox]);
+				if frame.data.collision.is_empty()
+					|| boxes.any(|b| b.is_some_and(CBox::is_empty))
+				{
+					return Err(MoveSetError::EmptyBox { name, index });
+				}
+			}
+
+			// Player state frames are `u8` and are incremented once past the last frame.
+			let len: u16 = data.iter().map(|d| d.duration as u16).sum();
+			if len >= u8::MAX as u16 {
+				return Err(MoveSetError::TooLong { name });
+			}
+		}
 
-pub const NNORMAL_DATA: [MoveData; 7] = [
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_0",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_1",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 100,
-			hitbox: cbox!(299),
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(324)],
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			hitbox: cbox!(299),
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(324)],
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(324)],
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_2",
-		duration: 10,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(237)],
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_3",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "nnormal_4",
-		duration: 2,
-	},
-];
+		Ok(())
+	}
 
-pub const MNORMAL_DATA: [MoveData; 7] = [
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_0",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_1",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 90,
-			hitbox: cbox!(260),
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(260)],
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			hitbox: cbox!(260),
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(260)],
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_2",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(260)],
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_2",
-		duration: 9,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(222)],
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_3",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mnormal_4",
-		duration: 2,
-	},
-];
+	#[inline]
+	pub fn attack(&self, attack: Attack) -> &[MoveData] {
+		match attack {
+			Attack::NNormal => &self.nnormal,
+			Attack::MNormal => &self.mnormal,
+			Attack::NSpecial => &self.nspecial,
+			Attack::MSpecial => &self.mspecial,
+		}
+	}
 
-pub const NSPECIAL_DATA: [MoveData; 12] = [
-	MoveData {
-		data: FrameData {
-			speed: 10,
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_0",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 32,
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_1",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 55,
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_2",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 21,
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_3",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 8,
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_4",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 4,
-			hitbox: cbox!(260),
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(280)],
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_5",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 4,
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(280)],
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_5",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 3,
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(280)],
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_5",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(254)],
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_5",
-		duration: 7,
-	},
-	MoveData {
-		data: FrameData {
-			hurtbox: [Some(CBox::base_hurtbox()), cbox!(240)],
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_6",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_6",
-		duration: 12,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "nspecial_7",
-		duration: 2,
-	},
-];
+	fn moves(&self) -> [(&'static str, &[MoveData]); 13] {
+		[
+			("idle", &self.idle),
+			("fwalk", &self.fwalk),
+			("bwalk", &self.bwalk),
+			("fdash", &self.fdash),
+			("bdash", &self.bdash),
+			("nnormal", &self.nnormal),
+			("mnormal", &self.mnormal),
+			("nspecial", &self.nspecial),
+			("mspecial", &self.mspecial),
+			("normal_dead", std::slice::from_ref(&self.normal_dead)),
+			("special_dead", std::slice::from_ref(&self.special_dead)),
+			("block", std::slice::from_ref(&self.block)),
+			("hitstun", std::slice::from_ref(&self.hitstun)),
+		]
+	}
+}
 
-pub const MSPECIAL_DATA: [MoveData; 11] = [
-	MoveData {
-		data: FrameData {
-			speed: 8,
-			hurtbox: [None, None],
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_0",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 8,
-			hurtbox: [None, None],
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_1",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 7,
-			hitbox: cbox!(190),
-			hurtbox: [None, None],
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 5,
-			hitbox: cbox!(190),
-			hurtbox: [None, None],
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_2",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 5,
-			hitbox: cbox!(190),
-			hurtbox: [None, None],
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_3",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 5,
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_3",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: 3,
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_3",
-		duration: 5,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_3",
-		duration: 20,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_4",
-		duration: 10,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_5",
-		duration: 7,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "mspecial_6",
-		duration: 2,
-	},
-];
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveSetError {
+	Empty { name: &'static str },
+	ZeroDuration { name: &'static str, index: usize },
+	EmptyBox { name: &'static str, index: usize },
+	TooLong { name: &'static str },
+}
 
-pub const FDASH_DATA: [MoveData; 9] = [
-	MoveData {
-		data: FrameData {
-			meter: 15,
-			speed: 13,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_0",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 20,
-			speed: 18,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_0",
-		duration: 5,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 20,
-			speed: 18,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_1",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 15,
-			speed: 12,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_1",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 15,
-			speed: 12,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 7,
-			speed: 5,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_2",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 7,
-			speed: 5,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_3",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			meter: 4,
-			speed: 3,
-			..FrameData::default()
-		},
-		animation_frame: "fdash_3",
-		duration: 1,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "fdash_4",
-		duration: 1,
-	},
-];
+impl fmt::Display for MoveSetError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MoveSetError::Empty { name } => write!(f, "`{name}` has no frames"),
+			MoveSetError::ZeroDuration { name, index } => {
+				write!(f, "`{name}` frame {index} has zero duration")
+			}
+			MoveSetError::EmptyBox { name, index } => {
+				write!(f, "`{name}` frame {index} has a box with no area, use `None` instead")
+			}
+			MoveSetError::TooLong { name } => {
+				write!(f, "`{name}` is longer than {} frames", u8::MAX - 1)
+			}
+		}
+	}
+}
 
-pub const BDASH_DATA: [MoveData; 8] = [
-	MoveData {
-		data: FrameData {
-			speed: -26,
-			..FrameData::default()
-		},
-		animation_frame: "bdash_0",
-		duration: 3,
-	},
-	MoveData {
-		data: FrameData {
-			speed: -12,
-			..FrameData::default()
-		},
-		animation_frame: "bdash_0",
-		duration: 6,
-	},
-	MoveData {
-		data: FrameData {
-			speed: -8,
-			..FrameData::default()
-		},
-		animation_frame: "bdash_0",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: -8,
-			..FrameData::default()
-		},
-		animation_frame: "bdash_1",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			speed: -3,
-			..FrameData::default()
-		},
-		animation_frame: "bdash_1",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "bdash_1",
-		duration: 2,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "bdash_2",
-		duration: 4,
-	},
-	MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "bdash_3",
-		duration: 1,
-	},
-];
+impl std::error::Error for MoveSetError {}
 
-pub fn move_data(frame: u8, data_array: &'static [MoveData]) -> Option<&'static MoveData> {
+pub fn move_data(frame: u8, data_array: &[MoveData]) -> Option<&MoveData> {
 	let mut frame = (frame + 1) as usize;
 
 	for d in data_array.iter() {
@@ -637,45 +178,46 @@ pub fn move_data(frame: u8, data_array: &'static [MoveData]) -> Option<&'static
 	None
 }
 
-#[inline]
-pub fn idle_data(frame: u8) -> Option<&'static MoveData> {
-	move_data(frame, &IDLE_DATA)
+pub fn move_length(data: &[MoveData]) -> u8 {
+	data.iter().map(|d| d.duration).sum()
 }
 
-pub fn normal_dead_data() -> &'static MoveData {
-	const DATA: MoveData = MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "hit_0",
-		duration: 1,
-	};
-
-	&DATA
-}
-
-pub fn special_dead_data() -> &'static MoveData {
-	const DATA: MoveData = MoveData {
-		data: FrameData {
-			..FrameData::default()
-		},
-		animation_frame: "dead_0",
-		duration: 1,
-	};
-
-	&DATA
-}
-
-#[allow(unused)]
-pub const fn move_length(data: &[MoveData]) -> u8 {
-	let mut i = 0;
-	let mut res = 0;
-
-	// Not used `for` to be const compatible
-	while i < data.len() {
-		res += data[i].duration;
-		i += 1;
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::character::CharacterId;
+
+	#[test]
+	fn validate() {
+		let moves = &CharacterId::DEFAULT.get().moves;
+		assert_eq!(moves.validate(), Ok(()));
+
+		let mut set = moves.clone();
+		set.nnormal[1].duration = 0;
+		assert_eq!(
+			set.validate(),
+			Err(MoveSetError::ZeroDuration {
+				name: "nnormal",
+				index: 1
+			})
+		);
+
+		let mut set = moves.clone();
+		set.fdash.clear();
+		assert_eq!(set.validate(), Err(MoveSetError::Empty { name: "fdash" }));
+
+		let mut set = moves.clone();
+		set.mnormal[2].data.hitbox = Some(CBox::new(0));
+		assert_eq!(
+			set.validate(),
+			Err(MoveSetError::EmptyBox {
+				name: "mnormal",
+				index: 2
+			})
+		);
+
+		let mut set = moves.clone();
+		set.bdash[0].duration = 250;
+		assert_eq!(set.validate(), Err(MoveSetError::TooLong { name: "bdash" }));
 	}
-
-	res
 }
@@ -0,0 +1,59 @@
+use godot::prelude::*;
+
+use super::{input::FgInput, simul::Match};
+use crate::bot::{self, BotKind, Difficulty, View};
+#[cfg(feature = "onnx")]
+use crate::{bot::PolicyBot, policy::Policy};
+
+/// Godot side of [`bot::Bot`], playing one side of a `Match`.
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct Bot {
+	bot: Box<dyn bot::Bot>,
+	p1: bool,
+}
+
+#[godot_api]
+impl Bot {
+	/// `seed` only matters to bots that make random choices and below [`Difficulty::Perfect`]. Take
+	/// it from the match setup so a match can be played again.
+	#[func]
+	pub fn gd_new(kind: BotKind, difficulty: Difficulty, p1: bool, seed: i64) -> Gd<Self> {
+		Gd::from_object(Bot {
+			bot: kind.realtime_bot_at(difficulty, seed as u64),
+			p1,
+		})
+	}
+
+	/// [`PolicyBot`] playing the ONNX model in `model`, read by the game, e.g. with
+	/// `FileAccess.get_file_as_bytes`. Returns `null` if the model can not be loaded.
+	#[cfg(feature = "onnx")]
+	#[func]
+	pub fn from_policy(model: PackedByteArray, p1: bool, seed: i64) -> Option<Gd<Self>> {
+		match Policy::from_bytes(model.as_slice()) {
+			Ok(policy) => Some(Gd::from_object(Bot {
+				bot: Box::new(PolicyBot::new(policy, seed as u64)),
+				p1,
+			})),
+			Err(err) => {
+				godot_error!("Could not load policy: {err}");
+				None
+			}
+		}
+	}
+
+	/// Input for the next `frame_update` of `sim`. Call it once per frame from
+	/// `_get_local_input`, never while rolling back, so the inputs get recorded and replayed like
+	/// those of a player.
+	#[func]
+	pub fn input(&mut self, sim: Gd<Match>) -> Gd<FgInput> {
+		let input = self.bot.input(View::new(sim.bind().sim(), self.p1));
+
+		FgInput::gd_new(input.movement, input.attack_press, input.special_press)
+	}
+
+	#[func]
+	pub fn is_p1(&self) -> bool {
+		self.p1
+	}
+}
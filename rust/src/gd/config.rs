@@ -0,0 +1,161 @@
+use godot::prelude::*;
+
+use crate::{config, timer::Timer};
+
+/// Godot side of [`config::MatchConfig`], as a resource so rule sets can be saved and edited in the
+/// inspector. A `round_time` of `0` means untimed rounds. The `guard_*` and `health_*` fields only
+/// apply with `guard` and `health` on.
+#[derive(Debug, GodotClass)]
+#[class(base = Resource)]
+pub struct MatchConfig {
+	#[export]
+	round_time: u16,
+	#[export]
+	wins_needed: u8,
+	#[export]
+	round_start_len: u8,
+	#[export]
+	hitstop_len: u8,
+	#[export]
+	round_end_len: u8,
+	#[export]
+	stage_len: i16,
+	#[export]
+	p1_start: i16,
+	#[export]
+	p2_start: i16,
+	#[export]
+	meter_carry_percent: u8,
+	#[export]
+	guard: bool,
+	#[export]
+	guard_blockstun_len: u8,
+	#[export]
+	guard_pushback: i16,
+	#[export]
+	guard_max: u8,
+	#[export]
+	guard_normal_damage: u8,
+	#[export]
+	guard_special_damage: u8,
+	#[export]
+	health: bool,
+	#[export]
+	health_max: u16,
+	#[export]
+	health_hitstun_len: u8,
+	#[export]
+	health_knockback: i16,
+	base: Base<Resource>,
+}
+
+#[godot_api]
+impl IResource for MatchConfig {
+	fn init(base: Base<Resource>) -> Self {
+		Self::from_config(config::MatchConfig::DEFAULT, base)
+	}
+}
+
+#[godot_api]
+impl MatchConfig {
+	/// Returns `null` if the config can not be parsed or is invalid.
+	#[func]
+	pub fn from_ron(ron: GString) -> Option<Gd<Self>> {
+		match config::MatchConfig::from_ron(&ron.to_string()) {
+			Ok(config) => Some(Self::new_gd_from(config)),
+			Err(err) => {
+				godot_error!("Could not load match config: {err}");
+				None
+			}
+		}
+	}
+
+	#[func]
+	pub fn to_ron(&self) -> GString {
+		self.to_config().to_ron().into()
+	}
+
+	#[func]
+	pub fn is_valid(&self) -> bool {
+		self.to_config().validate().is_ok()
+	}
+}
+
+impl MatchConfig {
+	fn from_config(config: config::MatchConfig, base: Base<Resource>) -> Self {
+		let guard = config.guard.unwrap_or(config::GuardConfig::DEFAULT);
+		let health = config.health.unwrap_or(config::HealthConfig::DEFAULT);
+
+		MatchConfig {
+			round_time: match config.round_time {
+				Timer::Limited(time) => time,
+				Timer::Unlimited => 0,
+			},
+			wins_needed: config.wins_needed,
+			round_start_len: config.round_start_len,
+			hitstop_len: config.hitstop_len,
+			round_end_len: config.round_end_len,
+			stage_len: config.stage_len,
+			p1_start: config.p1_start,
+			p2_start: config.p2_start,
+			meter_carry_percent: config.meter_carry_percent,
+			guard: config.guard.is_some(),
+			guard_blockstun_len: guard.blockstun_len,
+			guard_pushback: guard.pushback,
+			guard_max: guard.guard_max,
+			guard_normal_damage: guard.normal_damage,
+			guard_special_damage: guard.special_damage,
+			health: config.health.is_some(),
+			health_max: health.max_health,
+			health_hitstun_len: health.hitstun_len,
+			health_knockback: health.knockback,
+			base,
+		}
+	}
+
+	pub(super) fn new_gd_from(config: config::MatchConfig) -> Gd<Self> {
+		Gd::from_init_fn(|base| Self::from_config(config, base))
+	}
+
+	fn to_config(&self) -> config::MatchConfig {
+		config::MatchConfig {
+			round_time: match self.round_time {
+				0 => Timer::Unlimited,
+				time => Timer::Limited(time),
+			},
+			wins_needed: self.wins_needed,
+			round_start_len: self.round_start_len,
+			hitstop_len: self.hitstop_len,
+			round_end_len: self.round_end_len,
+			stage_len: self.stage_len,
+			p1_start: self.p1_start,
+			p2_start: self.p2_start,
+			meter_carry_percent: self.meter_carry_percent,
+			guard: self.guard.then_some(config::GuardConfig {
+				blockstun_len: self.guard_blockstun_len,
+				pushback: self.guard_pushback,
+				guard_max: self.guard_max,
+				normal_damage: self.guard_normal_damage,
+				special_damage: self.guard_special_damage,
+			}),
+			health: self.health.then_some(config::HealthConfig {
+				max_health: self.health_max,
+				hitstun_len: self.health_hitstun_len,
+				knockback: self.health_knockback,
+			}),
+		}
+	}
+
+	/// Falls back to the default rules instead of panicking on the first frame.
+	pub(super) fn valid_config(&self) -> config::MatchConfig {
+		let config = self.to_config();
+
+		match config.validate() {
+			Ok(()) => config,
+			Err(err) => {
+				godot_error!("Invalid match config, using the default one: {err}");
+				config::MatchConfig::DEFAULT
+			}
+		}
+	}
+}
@@ -0,0 +1,73 @@
+use godot::prelude::*;
+
+use crate::{
+	event::{self, Attack, EventKind, PlayerId},
+	simul::Result,
+};
+
+/// Godot side of [`event::Event`]. Getters for data the event does not carry return the first
+/// variant of their type, check `kind` first.
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct Event {
+	event: event::Event,
+}
+
+#[godot_api]
+impl Event {
+	#[func]
+	pub fn kind(&self) -> EventKind {
+		self.event.kind()
+	}
+
+	/// Player the event is about, the attacker for `Hit` and `Block`.
+	#[func]
+	pub fn player(&self) -> PlayerId {
+		self.event.player().unwrap_or(PlayerId::P1)
+	}
+
+	#[func]
+	pub fn defender(&self) -> PlayerId {
+		match self.event {
+			event::Event::Hit { defender, .. }
+			| event::Event::Block { defender, .. } => defender,
+			_ => PlayerId::P1,
+		}
+	}
+
+	/// Attack of `player`, the attack of player 1 for `Trade`.
+	#[func]
+	pub fn attack(&self) -> Attack {
+		self.event.attack().unwrap_or(Attack::NNormal)
+	}
+
+	/// Attack of player 2 for `Trade`.
+	#[func]
+	pub fn p2_attack(&self) -> Attack {
+		match self.event {
+			event::Event::Trade { p2_attack, .. } => p2_attack,
+			_ => Attack::NNormal,
+		}
+	}
+
+	/// Direction of `DashStarted`.
+	#[func]
+	pub fn forward(&self) -> bool {
+		matches!(self.event, event::Event::DashStarted { forward: true, .. })
+	}
+
+	/// Result of `RoundEnded`.
+	#[func]
+	pub fn result(&self) -> Result {
+		match self.event {
+			event::Event::RoundEnded { result } => result,
+			_ => Result::Continue,
+		}
+	}
+}
+
+impl Event {
+	pub(super) fn from_event(event: event::Event) -> Gd<Self> {
+		Gd::from_object(Event { event })
+	}
+}
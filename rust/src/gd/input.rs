@@ -0,0 +1,35 @@
+use godot::prelude::*;
+
+use crate::input;
+
+/// Godot side of [`input::FgInput`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct FgInput {
+	pub input: input::FgInput,
+}
+
+#[godot_api]
+impl FgInput {
+	#[func]
+	pub fn gd_new(movement: i8, attack_press: bool, special_press: bool) -> Gd<Self> {
+		Gd::from_object(FgInput {
+			input: input::FgInput::new(movement, attack_press, special_press),
+		})
+	}
+
+	#[func]
+	pub fn movement(&self) -> i8 {
+		self.input.movement
+	}
+
+	#[func]
+	pub fn attack_press(&self) -> bool {
+		self.input.attack_press
+	}
+
+	#[func]
+	pub fn special_press(&self) -> bool {
+		self.input.special_press
+	}
+}
@@ -0,0 +1,18 @@
+//! GDExtension bindings. These are thin wrappers around the headless simulator and should not hold
+//! any game logic of their own.
+
+mod bot;
+mod config;
+mod event;
+mod input;
+mod replay;
+mod rollback;
+mod simul;
+mod sound;
+
+use godot::prelude::*;
+
+struct MyExtension;
+
+#[gdextension]
+unsafe impl ExtensionLibrary for MyExtension {}
@@ -0,0 +1,134 @@
+use godot::prelude::*;
+
+use super::{input::FgInput, simul::Match};
+use crate::{replay, simul::Result};
+
+/// Godot side of [`replay::Recorder`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct ReplayRecorder {
+	recorder: replay::Recorder,
+}
+
+#[godot_api]
+impl ReplayRecorder {
+	#[func]
+	pub fn gd_new(initial: Gd<Match>) -> Gd<Self> {
+		Gd::from_object(ReplayRecorder {
+			recorder: replay::Recorder::new(initial.bind().sim()),
+		})
+	}
+
+	/// Call after every `frame_update` with its inputs and result.
+	#[func]
+	pub fn record(&mut self, input1: Gd<FgInput>, input2: Gd<FgInput>, result: Result) {
+		self.recorder
+			.record(input1.bind().input, input2.bind().input, result);
+	}
+
+	/// Binary replay up to now, `last` being the current state.
+	#[func]
+	pub fn to_bin(&self, last: Gd<Match>) -> PackedByteArray {
+		self.recorder
+			.clone()
+			.finish(last.bind().sim())
+			.to_bin()
+			.into()
+	}
+
+	#[func]
+	pub fn to_ron(&self, last: Gd<Match>) -> GString {
+		self.recorder
+			.clone()
+			.finish(last.bind().sim())
+			.to_ron()
+			.into()
+	}
+
+	#[func]
+	pub fn frame_count(&self) -> u32 {
+		self.recorder.len()
+	}
+}
+
+/// Godot side of [`replay::ReplayPlayer`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct ReplayPlayer {
+	player: replay::ReplayPlayer,
+}
+
+#[godot_api]
+impl ReplayPlayer {
+	/// Returns `null` if the replay can not be read.
+	#[func]
+	pub fn from_bin(from: PackedByteArray) -> Option<Gd<Self>> {
+		Self::load(replay::Replay::from_bin(from.as_slice()))
+	}
+
+	/// Returns `null` if the replay can not be read.
+	#[func]
+	pub fn from_ron(from: GString) -> Option<Gd<Self>> {
+		Self::load(replay::Replay::from_ron(&from.to_string()))
+	}
+
+	/// Returns `Result::Pause` once the replay is over, check `is_finished`.
+	#[func]
+	pub fn step(&mut self) -> Result {
+		self.player.step().unwrap_or(Result::Pause)
+	}
+
+	#[func]
+	pub fn seek(&mut self, frame: u32) {
+		self.player.seek(frame);
+	}
+
+	#[func]
+	pub fn is_finished(&self) -> bool {
+		self.player.frame() >= self.player.replay().len()
+	}
+
+	/// Copy of the current state, for drawing.
+	#[func]
+	pub fn current_match(&self) -> Gd<Match> {
+		Match::from_sim(self.player.sim().clone())
+	}
+
+	#[func]
+	pub fn frame(&self) -> u32 {
+		self.player.frame()
+	}
+
+	#[func]
+	pub fn frame_count(&self) -> u32 {
+		self.player.replay().len()
+	}
+
+	/// Replays the whole match from the start and checks it plays out as recorded.
+	#[func]
+	pub fn verify(&self) -> bool {
+		match self.player.replay().verify() {
+			Ok(()) => true,
+			Err(err) => {
+				godot_error!("Replay does not match: {err}");
+				false
+			}
+		}
+	}
+}
+
+impl ReplayPlayer {
+	fn load(
+		replay: std::result::Result<replay::Replay, replay::ReplayError>,
+	) -> Option<Gd<Self>> {
+		match replay {
+			Ok(replay) => Some(Gd::from_object(ReplayPlayer {
+				player: replay::ReplayPlayer::new(replay),
+			})),
+			Err(err) => {
+				godot_error!("Could not load replay: {err}");
+				None
+			}
+		}
+	}
+}
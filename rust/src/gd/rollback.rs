@@ -0,0 +1,70 @@
+use godot::prelude::*;
+
+use super::{input::FgInput, simul::Match};
+use crate::{rollback, simul::Result};
+
+/// Godot side of [`rollback::RollbackSession`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct RollbackSession {
+	session: rollback::RollbackSession,
+}
+
+#[godot_api]
+impl RollbackSession {
+	/// See [`rollback::RollbackSession::new`], `0` keeps to confirmed frames.
+	#[func]
+	pub fn gd_new(sim: Gd<Match>, max_prediction: u32) -> Gd<Self> {
+		Gd::from_object(RollbackSession {
+			session: rollback::RollbackSession::new(
+				sim.bind().sim().clone(),
+				max_prediction,
+			),
+		})
+	}
+
+	/// Returns `false` if the input is not the next one expected for this player.
+	#[func]
+	pub fn add_input(&mut self, p1: bool, frame: u32, input: Gd<FgInput>) -> bool {
+		match self.session.add_input(p1, frame, input.bind().input) {
+			Ok(()) => true,
+			Err(err) => {
+				godot_error!("Could not add input: {err}");
+				false
+			}
+		}
+	}
+
+	/// Returns `Result::Pause` without simulating while too far ahead of the remote player, check
+	/// `can_advance` to tell this apart from an actual pause.
+	#[func]
+	pub fn advance_frame(&mut self) -> Result {
+		self.session.advance_frame().unwrap_or(Result::Pause)
+	}
+
+	#[func]
+	pub fn can_advance(&self) -> bool {
+		self.session.can_advance()
+	}
+
+	#[func]
+	pub fn synchronize(&mut self) -> u32 {
+		self.session.synchronize()
+	}
+
+	/// Copy of the current state, for drawing.
+	#[func]
+	pub fn current_match(&self) -> Gd<Match> {
+		Match::from_sim(self.session.sim().clone())
+	}
+
+	#[func]
+	pub fn frame(&self) -> u32 {
+		self.session.frame()
+	}
+
+	#[func]
+	pub fn confirmed_frame(&self) -> u32 {
+		self.session.confirmed_frame()
+	}
+}
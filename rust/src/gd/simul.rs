@@ -0,0 +1,454 @@
+use godot::prelude::*;
+
+use super::{config::MatchConfig, event::Event, input::FgInput, sound::SoundEvent};
+use crate::{
+	cbox::WorldBox,
+	character::{self, Character, CharacterId},
+	desync,
+	event::Attack,
+	simul::{self, Result, StateError},
+	training::{Dummy, PlaybackTrigger, PositionPreset, Training},
+};
+
+/// Godot side of [`simul::Match`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct Match {
+	sim: simul::Match,
+}
+
+#[godot_api]
+impl Match {
+	#[func]
+	pub fn gd_new(p1_bot: bool, p2_bot: bool) -> Gd<Self> {
+		Gd::from_object(Match {
+			sim: simul::Match::new(p1_bot, p2_bot),
+		})
+	}
+
+	#[func]
+	pub fn gd_new_with_characters(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: u8,
+		p2_char: u8,
+	) -> Gd<Self> {
+		Gd::from_object(Match {
+			sim: simul::Match::with_characters(
+				p1_bot,
+				p2_bot,
+				registered_character(p1_char),
+				registered_character(p2_char),
+			),
+		})
+	}
+
+	#[func]
+	pub fn gd_new_with_config(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: u8,
+		p2_char: u8,
+		config: Gd<MatchConfig>,
+	) -> Gd<Self> {
+		Gd::from_object(Match {
+			sim: simul::Match::with_config(
+				p1_bot,
+				p2_bot,
+				registered_character(p1_char),
+				registered_character(p2_char),
+				config.bind().valid_config(),
+			),
+		})
+	}
+
+	/// Practice match, see [`Training`].
+	#[func]
+	pub fn gd_new_training(p1_char: u8, p2_char: u8) -> Gd<Self> {
+		Gd::from_object(Match {
+			sim: simul::Match::with_characters(
+				false,
+				false,
+				registered_character(p1_char),
+				registered_character(p2_char),
+			)
+			.with_training(Training::new()),
+		})
+	}
+
+	#[func]
+	pub fn is_training(&self) -> bool {
+		self.sim.training().is_some()
+	}
+
+	#[func]
+	pub fn set_meter_lock(&mut self, p1: bool, locked: bool) {
+		if let Some(training) = self.training_mut() {
+			training.lock_meter[(!p1) as usize] = locked;
+		}
+	}
+
+	#[func]
+	pub fn set_recovery_frames(&mut self, frames: u8) {
+		if let Some(training) = self.training_mut() {
+			training.recovery_frames = frames;
+		}
+	}
+
+	/// Records the next `frames` inputs of player 2 into `slot`.
+	#[func]
+	pub fn start_dummy_recording(&mut self, slot: u8, frames: u16) {
+		if slot as usize >= Dummy::SLOT_COUNT {
+			godot_error!("Dummy slot {slot} does not exist.");
+			return;
+		}
+
+		if let Some(training) = self.training_mut() {
+			training.dummy.record(slot, frames);
+		}
+	}
+
+	/// Returns `false` if no slot to play has been recorded.
+	#[func]
+	pub fn start_dummy_playback(&mut self, trigger: PlaybackTrigger) -> bool {
+		self.training_mut()
+			.is_some_and(|training| training.dummy.play(trigger))
+	}
+
+	#[func]
+	pub fn stop_dummy(&mut self) {
+		if let Some(training) = self.training_mut() {
+			training.dummy.stop();
+		}
+	}
+
+	#[func]
+	pub fn set_dummy_slot(&mut self, slot: u8) {
+		if slot as usize >= Dummy::SLOT_COUNT {
+			godot_error!("Dummy slot {slot} does not exist.");
+			return;
+		}
+
+		if let Some(training) = self.training_mut() {
+			training.dummy.playback_slot = slot;
+		}
+	}
+
+	#[func]
+	pub fn set_dummy_random_slot(&mut self, random: bool) {
+		if let Some(training) = self.training_mut() {
+			training.dummy.random_slot = random;
+		}
+	}
+
+	#[func]
+	pub fn is_dummy_recording(&self) -> bool {
+		self.sim.training()
+			.is_some_and(|training| training.dummy.is_recording())
+	}
+
+	#[func]
+	pub fn is_dummy_playing(&self) -> bool {
+		self.sim.training()
+			.is_some_and(|training| training.dummy.is_playing())
+	}
+
+	#[func]
+	pub fn reset_positions(&mut self, preset: PositionPreset) {
+		self.sim.reset_positions(preset);
+	}
+
+	/// Copy of the rules this match runs with.
+	#[func]
+	pub fn config(&self) -> Gd<MatchConfig> {
+		MatchConfig::new_gd_from(*self.sim.config())
+	}
+
+	/// Registers a character from its RON source. Returns the character id to pass to
+	/// `gd_new_with_characters`, or `-1` if the character is invalid.
+	#[func]
+	pub fn register_character(ron: GString) -> i64 {
+		match Character::from_ron(&ron.to_string()).and_then(character::register_character)
+		{
+			Ok(id) => id.0 as i64,
+			Err(err) => {
+				godot_error!("Could not register character: {err}");
+				-1
+			}
+		}
+	}
+
+	/// Returns the id of the registered character with the given name, or `-1`.
+	#[func]
+	pub fn character_by_name(name: GString) -> i64 {
+		match CharacterId::by_name(&name.to_string()) {
+			Some(id) => id.0 as i64,
+			None => -1,
+		}
+	}
+
+	#[func]
+	pub fn new_round(&mut self) {
+		self.sim.new_round();
+	}
+
+	#[func]
+	pub fn frame_update(&mut self, input1: Gd<FgInput>, input2: Gd<FgInput>) -> Result {
+		let input1 = input1.bind().input;
+		let input2 = input2.bind().input;
+
+		self.sim.frame_update(input1, input2)
+	}
+
+	#[func]
+	pub fn player_relative_pos(&self, p1: bool) -> i16 {
+		self.sim.player_relative_pos(p1)
+	}
+
+	#[func]
+	pub fn player_distance(&self) -> i16 {
+		self.sim.player_distance()
+	}
+
+	#[func]
+	pub fn p1_pos(&self) -> i16 {
+		self.sim.p1_pos()
+	}
+
+	#[func]
+	pub fn p2_pos(&self) -> i16 {
+		self.sim.p2_pos()
+	}
+
+	/// Collision box of the current frame in stage coordinates, mirrored for player 2 already. The
+	/// ground is at `y = 0`.
+	#[func]
+	pub fn collision_box(&self, p1: bool) -> Rect2i {
+		rect(self.sim.collision_box(p1))
+	}
+
+	/// Same as `collision_box`, for each hurtbox.
+	#[func]
+	pub fn hurtboxes(&self, p1: bool) -> Vec<Rect2i> {
+		self.sim.hurtboxes(p1).map(rect).collect()
+	}
+
+	/// Same as `collision_box`, an empty rectangle without a hitbox this frame.
+	#[func]
+	pub fn hitbox(&self, p1: bool) -> Rect2i {
+		self.sim.hitbox(p1).map(rect).unwrap_or_default()
+	}
+
+	#[func]
+	pub fn p1_sprite(&self) -> GString {
+		GString::from(self.sim.p1_sprite())
+	}
+
+	#[func]
+	pub fn p2_sprite(&self) -> GString {
+		GString::from(self.sim.p2_sprite())
+	}
+
+	#[func]
+	pub fn p1_wins(&self) -> u8 {
+		self.sim.p1_wins()
+	}
+
+	#[func]
+	pub fn p2_wins(&self) -> u8 {
+		self.sim.p2_wins()
+	}
+
+	#[func]
+	pub fn p1_meter(&self) -> u16 {
+		self.sim.p1_meter()
+	}
+
+	#[func]
+	pub fn p2_meter(&self) -> u16 {
+		self.sim.p2_meter()
+	}
+
+	#[func]
+	pub fn player_character(&self, p1: bool) -> u8 {
+		self.sim.player_character(p1).0
+	}
+
+	#[func]
+	pub fn player_state(&self, p1: bool) -> i64 {
+		self.sim.player_state(p1)
+	}
+
+	#[func]
+	pub fn player_state_len(&self, p1: bool) -> i64 {
+		self.sim.player_state_len(p1)
+	}
+
+	#[func]
+	pub fn player_dead(&self, p1: bool) -> bool {
+		self.sim.player_dead(p1)
+	}
+
+	/// Guard points left this round, `0` without guard rules.
+	#[func]
+	pub fn guard_left(&self, p1: bool) -> u8 {
+		self.sim.guard_left(p1)
+	}
+
+	/// Health left this round, `0` without health rules.
+	#[func]
+	pub fn health_left(&self, p1: bool) -> u16 {
+		self.sim.health_left(p1)
+	}
+
+	/// Whether the player can start `attack` now and hit the opponent before it recovers.
+	#[func]
+	pub fn can_punish(&self, p1: bool, attack: Attack) -> bool {
+		self.sim.can_punish(p1, attack)
+	}
+
+	#[func]
+	pub fn timer_sec(&self) -> u16 {
+		self.sim.timer_sec()
+	}
+
+	#[func]
+	pub fn rounds(&self) -> u8 {
+		self.sim.rounds()
+	}
+
+	#[func]
+	pub fn audio(&self) -> Vec<Gd<SoundEvent>> {
+		self.sim.audio()
+			.into_iter()
+			.map(SoundEvent::from_sound)
+			.collect()
+	}
+
+	/// What happened during the last `frame_update`, in order.
+	#[func]
+	pub fn events(&self) -> Vec<Gd<Event>> {
+		self.sim.events()
+			.iter()
+			.copied()
+			.map(Event::from_event)
+			.collect()
+	}
+
+	#[func]
+	pub fn continues(&self) -> bool {
+		self.sim.continues()
+	}
+
+	#[func]
+	pub fn state(&self) -> i64 {
+		self.sim.state()
+	}
+
+	#[func]
+	pub fn state_len(&self) -> i64 {
+		self.sim.state_len()
+	}
+
+	#[func]
+	pub fn stage_len(&self) -> i16 {
+		self.sim.stage_len()
+	}
+
+	#[func]
+	pub fn serialize_ron(&self) -> String {
+		self.sim.serialize_ron()
+	}
+
+	/// Returns `0` on success, like Godot's `OK`, or the [`StateError::code`] of the failure. The
+	/// match is left unchanged on failure.
+	#[func]
+	pub fn deserialize_ron(&mut self, from: GString) -> i64 {
+		state_error_code(self.sim.deserialize_ron(&from.to_string()))
+	}
+
+	#[func]
+	pub fn serialize_bin(&self) -> PackedByteArray {
+		self.sim.serialize_bin().into()
+	}
+
+	/// Same return value as `deserialize_ron`.
+	#[func]
+	pub fn deserialize_bin(&mut self, from: PackedByteArray) -> i64 {
+		state_error_code(self.sim.deserialize_bin(from.as_slice()))
+	}
+
+	/// Bit pattern of the `u64` checksum, Godot integers are signed.
+	#[func]
+	pub fn checksum(&self) -> i64 {
+		self.sim.checksum() as i64
+	}
+
+	/// Differing fields of two states from `serialize_bin`, one per line. Empty if they are equal.
+	#[func]
+	pub fn desync_report(left: PackedByteArray, right: PackedByteArray) -> GString {
+		match desync::desync_report_bin(left.as_slice(), right.as_slice()) {
+			Ok(diffs) => diffs
+				.iter()
+				.map(ToString::to_string)
+				.collect::<Vec<_>>()
+				.join("\n")
+				.into(),
+			Err(err) => {
+				godot_error!("Could not decode state: {err}");
+				GString::new()
+			}
+		}
+	}
+}
+
+impl Match {
+	pub(super) fn from_sim(sim: simul::Match) -> Gd<Self> {
+		Gd::from_object(Match { sim })
+	}
+
+	pub(super) fn sim(&self) -> &simul::Match {
+		&self.sim
+	}
+
+	fn training_mut(&mut self) -> Option<&mut Training> {
+		let training = self.sim.training_mut();
+		if training.is_none() {
+			godot_error!("Match is not in training mode.");
+		}
+
+		training
+	}
+}
+
+fn state_error_code(res: std::result::Result<(), StateError>) -> i64 {
+	match res {
+		Ok(()) => 0,
+		Err(err) => {
+			godot_error!("Could not load match state: {err}");
+			err.code()
+		}
+	}
+}
+
+/// Godot's y axis points down, so the rectangle goes from `-y` up to `-y - height`.
+fn rect(world: WorldBox) -> Rect2i {
+	Rect2i::new(
+		Vector2i::new(world.x as i32, -(world.y as i32) - world.height as i32),
+		Vector2i::new(world.width as i32, world.height as i32),
+	)
+}
+
+/// Falls back to the default character instead of panicking on the first frame.
+fn registered_character(id: u8) -> CharacterId {
+	let id = CharacterId(id);
+	if id.is_registered() {
+		id
+	} else {
+		godot_error!(
+			"Character {} is not registered, using the default one.",
+			id.0
+		);
+		CharacterId::DEFAULT
+	}
+}
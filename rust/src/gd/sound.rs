@@ -0,0 +1,44 @@
+use godot::prelude::*;
+
+use crate::{
+	event::PlayerId,
+	sound::{self, SoundCue},
+};
+
+/// Godot side of [`sound::SoundEvent`].
+#[derive(Debug, GodotClass)]
+#[class(no_init)]
+pub struct SoundEvent {
+	sound: sound::SoundEvent,
+}
+
+#[godot_api]
+impl SoundEvent {
+	#[func]
+	pub fn player(&self) -> PlayerId {
+		self.sound.player
+	}
+
+	#[func]
+	pub fn cue(&self) -> SoundCue {
+		self.sound.cue
+	}
+
+	/// Name of the audio file for the cue, without extension.
+	#[func]
+	pub fn name(&self) -> GString {
+		GString::from(self.sound.cue.name())
+	}
+
+	/// `1:nnormal` for `NNormal` of player 1, unique within a frame.
+	#[func]
+	pub fn id(&self) -> GString {
+		GString::from(self.sound.to_string())
+	}
+}
+
+impl SoundEvent {
+	pub(super) fn from_sound(sound: sound::SoundEvent) -> Gd<Self> {
+		Gd::from_object(SoundEvent { sound })
+	}
+}
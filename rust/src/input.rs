@@ -3,25 +3,19 @@ use std::{
 	ops::Sub,
 };
 
-use godot::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::event::Attack;
+
 /// Inputs with attack overweighs Inputs with no attack.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, GodotClass)]
-#[class(no_init)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct FgInput {
 	pub movement: i8,
 	pub attack_press: bool,
 	pub special_press: bool,
 }
 
-#[godot_api]
 impl FgInput {
-	#[func]
-	pub fn gd_new(movement: i8, attack_press: bool, special_press: bool) -> Gd<Self> {
-		Gd::from_object(Self::new(movement, attack_press, special_press))
-	}
-
 	pub const fn new(movement: i8, attack_press: bool, special_press: bool) -> Self {
 		FgInput {
 			movement,
@@ -30,6 +24,16 @@ impl FgInput {
 		}
 	}
 
+	/// Input that starts `attack` out of a neutral state.
+	pub const fn attack(attack: Attack) -> Self {
+		match attack {
+			Attack::NNormal => FgInput::new(0, true, false),
+			Attack::MNormal => FgInput::new(1, true, false),
+			Attack::NSpecial => FgInput::new(0, false, true),
+			Attack::MSpecial => FgInput::new(1, false, true),
+		}
+	}
+
 	pub const fn to_attack_buffer(self) -> Option<ActionBuffer> {
 		ActionBuffer::new(self.movement, self.attack_press)
 	}
@@ -1,13 +1,28 @@
-mod cbox;
-mod framedata;
-mod input;
-mod player;
-mod simul;
-mod timer;
+pub mod analysis;
+pub mod batch;
+pub mod bot;
+pub mod cbox;
+pub mod character;
+pub mod config;
+pub mod desync;
+pub mod env;
+pub mod event;
+pub mod framedata;
+pub mod input;
+pub mod player;
+#[cfg(feature = "onnx")]
+pub mod policy;
+pub mod replay;
+pub mod rng;
+pub mod rollback;
+pub mod script;
+pub mod simul;
+pub mod sound;
+pub mod timer;
+pub mod training;
 
-use godot::prelude::*;
+#[cfg(feature = "godot")]
+mod gd;
 
-struct MyExtension;
-
-#[gdextension]
-unsafe impl ExtensionLibrary for MyExtension {}
+#[cfg(feature = "python")]
+mod py;
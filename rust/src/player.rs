@@ -3,9 +3,11 @@ use std::ops::Add;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+	character::CharacterId,
+	config::MatchConfig,
 	framedata::*,
 	input::{ActionBuffer, FgInput},
-	simul::Match,
+	sound::SoundCue,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +24,11 @@ pub struct Player {
 	movement: i8,
 	last_dir: i8,
 	bot: bool,
+	character: CharacterId,
+	/// Guard points taken by blocked attacks this round.
+	guard_damage: u8,
+	/// Health taken by hits this round.
+	damage: u16,
 }
 
 impl Player {
@@ -29,7 +36,7 @@ impl Player {
 	const BOT_DASH_TIME: u8 = 3;
 
 	#[inline]
-	pub const fn new(start_pos: i16, bot: bool) -> Self {
+	pub const fn new(start_pos: i16, bot: bool, character: CharacterId) -> Self {
 		Player {
 			position: start_pos,
 			wins: 0,
@@ -43,15 +50,28 @@ impl Player {
 			movement: 0,
 			last_dir: 0,
 			bot,
+			character,
+			guard_damage: 0,
+			damage: 0,
 		}
 	}
 
 	#[inline]
-	pub const fn reset(&mut self, start_pos: i16) {
+	pub const fn reset(&mut self, config: &MatchConfig, p1: bool) {
 		*self = Player {
 			wins: self.wins,
-			meter: self.meter * 2 / 4,
-			..Player::new(start_pos, self.bot)
+			meter: config.carried_meter(self.meter),
+			..Player::new(config.start(p1), self.bot, self.character)
+		};
+	}
+
+	/// Back on their feet at `position`, keeping wins and meter.
+	#[inline]
+	pub const fn respawn(&mut self, position: i16) {
+		*self = Player {
+			wins: self.wins,
+			meter: self.meter,
+			..Player::new(position, self.bot, self.character)
 		};
 	}
 
@@ -120,6 +140,14 @@ impl Player {
 			PlayerState::MSpecial(frame, hit) => PlayerState::MSpecial(frame + 1, hit),
 			PlayerState::NormalDead(_) => PlayerState::NormalDead(true),
 			PlayerState::SpecialDead(_) => PlayerState::SpecialDead(true),
+			PlayerState::Block(frame, len) if frame + 1 < len => {
+				PlayerState::Block(frame + 1, len)
+			}
+			PlayerState::Block(_, _) => PlayerState::Idle(0),
+			PlayerState::Hitstun(frame, len) if frame + 1 < len => {
+				PlayerState::Hitstun(frame + 1, len)
+			}
+			PlayerState::Hitstun(_, _) => PlayerState::Idle(0),
 		}
 	}
 
@@ -159,81 +187,85 @@ impl Player {
 	}
 
 	pub fn update_move(&mut self) -> &'static MoveData {
+		let set = self.moves();
+
 		match self.state {
 			PlayerState::Idle(frame) => {
-				if let Some(data) = idle_data(frame) {
+				if let Some(data) = move_data(frame, &set.idle) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::FWalk(frame) => {
-				if let Some(data) = move_data(frame, &FWALK_DATA) {
+				if let Some(data) = move_data(frame, &set.fwalk) {
 					data
 				} else {
 					self.state = PlayerState::FWalk(0);
-					move_data(0, &FWALK_DATA).unwrap()
+					move_data(0, &set.fwalk).unwrap()
 				}
 			}
 			PlayerState::BWalk(frame) => {
-				if let Some(data) = move_data(frame, &BWALK_DATA) {
+				if let Some(data) = move_data(frame, &set.bwalk) {
 					data
 				} else {
 					self.state = PlayerState::BWalk(0);
-					move_data(0, &BWALK_DATA).unwrap()
+					move_data(0, &set.bwalk).unwrap()
 				}
 			}
 			PlayerState::FDash(frame) => {
-				if let Some(data) = move_data(frame, &FDASH_DATA) {
+				if let Some(data) = move_data(frame, &set.fdash) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::BDash(frame) => {
-				if let Some(data) = move_data(frame, &BDASH_DATA) {
+				if let Some(data) = move_data(frame, &set.bdash) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::NNormal(frame, _) => {
-				if let Some(data) = move_data(frame, &NNORMAL_DATA) {
+				if let Some(data) = move_data(frame, &set.nnormal) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::MNormal(frame, _) => {
-				if let Some(data) = move_data(frame, &MNORMAL_DATA) {
+				if let Some(data) = move_data(frame, &set.mnormal) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::NSpecial(frame, _) => {
-				if let Some(data) = move_data(frame, &NSPECIAL_DATA) {
+				if let Some(data) = move_data(frame, &set.nspecial) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
 			PlayerState::MSpecial(frame, _) => {
-				if let Some(data) = move_data(frame, &MSPECIAL_DATA) {
+				if let Some(data) = move_data(frame, &set.mspecial) {
 					data
 				} else {
 					self.state = PlayerState::Idle(0);
-					idle_data(0).unwrap()
+					move_data(0, &set.idle).unwrap()
 				}
 			}
-			PlayerState::NormalDead(_) => normal_dead_data(),
-			PlayerState::SpecialDead(_) => special_dead_data(),
+			PlayerState::NormalDead(_) => &set.normal_dead,
+			PlayerState::SpecialDead(_) => &set.special_dead,
+			PlayerState::Block(_, _) => &set.block,
+			PlayerState::Hitstun(_, _) => &set.hitstun,
 		}
 	}
 
@@ -243,8 +275,8 @@ impl Player {
 	}
 
 	#[inline]
-	pub fn move_position(&mut self, movement: i16) {
-		self.position = (self.position + movement).clamp(0, Match::STAGE_LEN);
+	pub fn move_position(&mut self, movement: i16, stage_len: i16) {
+		self.position = self.position.saturating_add(movement).clamp(0, stage_len);
 	}
 
 	#[inline]
@@ -260,25 +292,65 @@ impl Player {
 	}
 
 	pub fn get_move(&self) -> &'static MoveData {
+		let set = self.moves();
+
 		match self.state {
-			PlayerState::Idle(frame) => move_data(frame, &IDLE_DATA).unwrap(),
-			PlayerState::FWalk(frame) => move_data(frame, &FWALK_DATA).unwrap(),
-			PlayerState::BWalk(frame) => move_data(frame, &BWALK_DATA).unwrap(),
-			PlayerState::FDash(frame) => move_data(frame, &FDASH_DATA).unwrap(),
-			PlayerState::BDash(frame) => move_data(frame, &BDASH_DATA).unwrap(),
-			PlayerState::NNormal(frame, _) => move_data(frame, &NNORMAL_DATA).unwrap(),
-			PlayerState::MNormal(frame, _) => move_data(frame, &MNORMAL_DATA).unwrap(),
-			PlayerState::NSpecial(frame, _) => {
-				move_data(frame, &NSPECIAL_DATA).unwrap()
-			}
-			PlayerState::MSpecial(frame, _) => {
-				move_data(frame, &MSPECIAL_DATA).unwrap()
-			}
-			PlayerState::NormalDead(_) => normal_dead_data(),
-			PlayerState::SpecialDead(_) => special_dead_data(),
+			PlayerState::Idle(frame) => move_data(frame, &set.idle).unwrap(),
+			PlayerState::FWalk(frame) => move_data(frame, &set.fwalk).unwrap(),
+			PlayerState::BWalk(frame) => move_data(frame, &set.bwalk).unwrap(),
+			PlayerState::FDash(frame) => move_data(frame, &set.fdash).unwrap(),
+			PlayerState::BDash(frame) => move_data(frame, &set.bdash).unwrap(),
+			PlayerState::NNormal(frame, _) => move_data(frame, &set.nnormal).unwrap(),
+			PlayerState::MNormal(frame, _) => move_data(frame, &set.mnormal).unwrap(),
+			PlayerState::NSpecial(frame, _) => move_data(frame, &set.nspecial).unwrap(),
+			PlayerState::MSpecial(frame, _) => move_data(frame, &set.mspecial).unwrap(),
+			PlayerState::NormalDead(_) => &set.normal_dead,
+			PlayerState::SpecialDead(_) => &set.special_dead,
+			PlayerState::Block(_, _) => &set.block,
+			PlayerState::Hitstun(_, _) => &set.hitstun,
 		}
 	}
 
+	/// Blocks an attack worth `damage` guard points, with `len` frames of blockstun.
+	pub fn block(&mut self, len: u8, damage: u8) {
+		self.state = PlayerState::Block(0, len);
+		self.guard_damage = self.guard_damage.saturating_add(damage);
+	}
+
+	/// Out of guard points for the rest of the round.
+	#[inline]
+	pub const fn break_guard(&mut self) {
+		self.guard_damage = u8::MAX;
+	}
+
+	#[inline]
+	pub const fn guard_damage(&self) -> u8 {
+		self.guard_damage
+	}
+
+	/// Holding back while standing or walking, or already blocking.
+	#[inline]
+	pub const fn is_blocking(&self) -> bool {
+		matches!(self.state, PlayerState::BWalk(_) | PlayerState::Block(_, _))
+	}
+
+	/// Hit for `damage` without getting knocked down, with `len` frames of hitstun.
+	pub fn stagger(&mut self, len: u8, damage: u16) {
+		self.state = PlayerState::Hitstun(0, len);
+		self.take_damage(damage);
+	}
+
+	#[inline]
+	pub fn take_damage(&mut self, damage: u16) {
+		self.damage = self.damage.saturating_add(damage);
+	}
+
+	/// Health taken this round.
+	#[inline]
+	pub const fn damage(&self) -> u16 {
+		self.damage
+	}
+
 	pub fn get_attacked(&mut self, special: bool) {
 		self.state = match special {
 			true => PlayerState::SpecialDead(false),
@@ -295,32 +367,53 @@ impl Player {
 	}
 
 	#[inline]
-	pub const fn recovery_punishable(&self) -> u8 {
+	pub fn recovery_punishable(&self) -> u8 {
+		let set = self.moves();
+
 		match self.state {
-			PlayerState::NNormal(frame, _) => move_length(&NNORMAL_DATA) - frame - 1,
-			PlayerState::MNormal(frame, _) => move_length(&MNORMAL_DATA) - frame - 1,
-			PlayerState::NSpecial(frame, _) => move_length(&NSPECIAL_DATA) - frame - 1,
-			PlayerState::MSpecial(frame, _) => move_length(&MSPECIAL_DATA) - frame - 1,
-			PlayerState::FDash(frame) => move_length(&FDASH_DATA) - frame - 1,
+			PlayerState::NNormal(frame, _) => move_length(&set.nnormal) - frame - 1,
+			PlayerState::MNormal(frame, _) => move_length(&set.mnormal) - frame - 1,
+			PlayerState::NSpecial(frame, _) => move_length(&set.nspecial) - frame - 1,
+			PlayerState::MSpecial(frame, _) => move_length(&set.mspecial) - frame - 1,
+			PlayerState::FDash(frame) => move_length(&set.fdash) - frame - 1,
 			_ => 0,
 		}
 	}
 
+	/// Frames of the move the player cannot act out of and the frame it is on, for the states
+	/// counted by [`Player::recovery_punishable`].
+	pub fn punishable_move(&self) -> Option<(&'static [MoveData], u8)> {
+		let set = self.moves();
+
+		match self.state {
+			PlayerState::NNormal(frame, _) => Some((&set.nnormal, frame)),
+			PlayerState::MNormal(frame, _) => Some((&set.mnormal, frame)),
+			PlayerState::NSpecial(frame, _) => Some((&set.nspecial, frame)),
+			PlayerState::MSpecial(frame, _) => Some((&set.mspecial, frame)),
+			PlayerState::FDash(frame) => Some((&set.fdash, frame)),
+			_ => None,
+		}
+	}
+
 	#[inline]
 	#[allow(unused)]
-	pub const fn recovery(&self) -> u8 {
+	pub fn recovery(&self) -> u8 {
+		let set = self.moves();
+
 		match self.state {
 			PlayerState::Idle(_) => 0,
 			PlayerState::FWalk(_) => 0,
 			PlayerState::BWalk(_) => 0,
-			PlayerState::FDash(frame) => move_length(&FDASH_DATA) - frame - 1,
-			PlayerState::BDash(frame) => move_length(&BDASH_DATA) - frame - 1,
-			PlayerState::NNormal(frame, _) => move_length(&NNORMAL_DATA) - frame - 1,
-			PlayerState::MNormal(frame, _) => move_length(&MNORMAL_DATA) - frame - 1,
-			PlayerState::NSpecial(frame, _) => move_length(&NSPECIAL_DATA) - frame - 1,
-			PlayerState::MSpecial(frame, _) => move_length(&MSPECIAL_DATA) - frame - 1,
+			PlayerState::FDash(frame) => move_length(&set.fdash) - frame - 1,
+			PlayerState::BDash(frame) => move_length(&set.bdash) - frame - 1,
+			PlayerState::NNormal(frame, _) => move_length(&set.nnormal) - frame - 1,
+			PlayerState::MNormal(frame, _) => move_length(&set.mnormal) - frame - 1,
+			PlayerState::NSpecial(frame, _) => move_length(&set.nspecial) - frame - 1,
+			PlayerState::MSpecial(frame, _) => move_length(&set.mspecial) - frame - 1,
 			PlayerState::NormalDead(_) => 0,
 			PlayerState::SpecialDead(_) => 0,
+			PlayerState::Block(frame, len) => len - frame - 1,
+			PlayerState::Hitstun(frame, len) => len - frame - 1,
 		}
 	}
 
@@ -379,20 +472,48 @@ impl Player {
 	}
 
 	#[inline]
-	pub const fn get_audio(&self) -> Option<&'static str> {
+	pub const fn sound_cue(&self) -> Option<SoundCue> {
 		match self.state {
-			PlayerState::FDash(0) => Some("fdash"),
-			PlayerState::BDash(0) => Some("bdash"),
-			PlayerState::NNormal(0, _) => Some("nnormal"),
-			PlayerState::MNormal(0, _) => Some("mnormal"),
-			PlayerState::NSpecial(0, _) => Some("nspecial"),
-			PlayerState::MSpecial(0, _) => Some("mspecial"),
-			PlayerState::NormalDead(false) => Some("ender_hit"),
-			PlayerState::SpecialDead(false) => Some("ender_hit"),
+			PlayerState::FDash(0) => Some(SoundCue::FDash),
+			PlayerState::BDash(0) => Some(SoundCue::BDash),
+			PlayerState::NNormal(0, _) => Some(SoundCue::NNormal),
+			PlayerState::MNormal(0, _) => Some(SoundCue::MNormal),
+			PlayerState::NSpecial(0, _) => Some(SoundCue::NSpecial),
+			PlayerState::MSpecial(0, _) => Some(SoundCue::MSpecial),
+			PlayerState::NormalDead(false) => Some(SoundCue::EnderHit),
+			PlayerState::SpecialDead(false) => Some(SoundCue::EnderHit),
+			PlayerState::Block(0, _) => Some(SoundCue::Block),
+			PlayerState::Hitstun(0, _) => Some(SoundCue::Hit),
 			_ => None,
 		}
 	}
 
+	#[inline]
+	pub const fn state(&self) -> PlayerState {
+		self.state
+	}
+
+	/// Total length of the current state, `1` for the dead states.
+	pub fn state_length(&self) -> u8 {
+		let set = self.moves();
+
+		match self.state {
+			PlayerState::Idle(_) => move_length(&set.idle),
+			PlayerState::FWalk(_) => move_length(&set.fwalk),
+			PlayerState::BWalk(_) => move_length(&set.bwalk),
+			PlayerState::FDash(_) => move_length(&set.fdash),
+			PlayerState::BDash(_) => move_length(&set.bdash),
+			PlayerState::NNormal(_, _) => move_length(&set.nnormal),
+			PlayerState::MNormal(_, _) => move_length(&set.mnormal),
+			PlayerState::NSpecial(_, _) => move_length(&set.nspecial),
+			PlayerState::MSpecial(_, _) => move_length(&set.mspecial),
+			PlayerState::NormalDead(_) => 1,
+			PlayerState::SpecialDead(_) => 1,
+			PlayerState::Block(_, len) => len,
+			PlayerState::Hitstun(_, len) => len,
+		}
+	}
+
 	#[inline]
 	pub fn state_int(&self) -> i64 {
 		self.state.into()
@@ -430,6 +551,16 @@ impl Player {
 		}
 	}
 
+	#[inline]
+	pub const fn character(&self) -> CharacterId {
+		self.character
+	}
+
+	#[inline]
+	pub fn moves(&self) -> &'static MoveSet {
+		&self.character.get().moves
+	}
+
 	#[inline]
 	const fn dash_time(&self) -> u8 {
 		match self.bot {
@@ -452,9 +583,35 @@ pub enum PlayerState {
 	MSpecial(u8, bool),
 	NormalDead(bool),
 	SpecialDead(bool),
+	/// Frame and length of the blockstun.
+	Block(u8, u8),
+	/// Frame and length of the hitstun.
+	Hitstun(u8, u8),
 }
 
 impl PlayerState {
+	pub const COUNT: usize = 13;
+
+	/// Dense index of the variant, in `0..PlayerState::COUNT`.
+	#[inline]
+	pub const fn index(self) -> usize {
+		match self {
+			PlayerState::Idle(_) => 0,
+			PlayerState::FWalk(_) => 1,
+			PlayerState::BWalk(_) => 2,
+			PlayerState::FDash(_) => 3,
+			PlayerState::BDash(_) => 4,
+			PlayerState::NNormal(_, _) => 5,
+			PlayerState::MNormal(_, _) => 6,
+			PlayerState::NSpecial(_, _) => 7,
+			PlayerState::MSpecial(_, _) => 8,
+			PlayerState::NormalDead(_) => 9,
+			PlayerState::SpecialDead(_) => 10,
+			PlayerState::Block(_, _) => 11,
+			PlayerState::Hitstun(_, _) => 12,
+		}
+	}
+
 	#[inline]
 	fn state_len(self) -> u8 {
 		match self {
@@ -469,6 +626,8 @@ impl PlayerState {
 			PlayerState::MSpecial(f, _) => f,
 			PlayerState::NormalDead(_) => 0,
 			PlayerState::SpecialDead(_) => 0,
+			PlayerState::Block(f, _) => f,
+			PlayerState::Hitstun(f, _) => f,
 		}
 	}
 }
@@ -486,6 +645,8 @@ impl From<PlayerState> for i64 {
 			PlayerState::MNormal(_, _) => 9,
 			PlayerState::NSpecial(_, _) => 10,
 			PlayerState::MSpecial(_, _) => 11,
+			PlayerState::Block(_, _) => 5,
+			PlayerState::Hitstun(_, _) => 6,
 			PlayerState::NormalDead(_) => 12,
 			PlayerState::SpecialDead(_) => 13,
 		}
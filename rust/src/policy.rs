@@ -0,0 +1,358 @@
+//! Policies trained on [`Env`](crate::env::Env) observations, run on the CPU with tract.
+//!
+//! A model takes a batch of [`Observation`]s as its only input and gives one logit per action of
+//! [`action_input`](env::action_input). Models exported with their action sampling, like the ones
+//! of sample-factory, are cut right before it. Actions are then either the most likely ones or
+//! sampled with an [`Rng`], so the same match and seed always get the same inputs.
+//!
+//! Models are read at runtime, from a file or from bytes the caller loaded, so the Godot game can
+//! hand over a model from its own resources.
+
+use std::{
+	fmt,
+	fs::File,
+	io::{self, Read},
+	path::Path,
+	sync::Arc,
+};
+
+use tract_onnx::{
+	prelude::*,
+	tract_hir::{infer::Factoid, internal::DimLike},
+};
+
+use crate::{
+	env::{self, Observation, ACTION_COUNT, OBS_LEN},
+	input::FgInput,
+	rng::Rng,
+};
+
+/// Loaded and optimized model, cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Policy {
+	plan: Arc<TypedSimplePlan<TypedModel>>,
+}
+
+impl Policy {
+	/// Loads an ONNX model.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+		Self::from_reader(&mut File::open(path)?)
+	}
+
+	/// Loads an ONNX model already read into memory.
+	pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, PolicyError> {
+		Self::from_reader(&mut bytes)
+	}
+
+	pub fn from_reader(reader: &mut impl Read) -> Result<Self, PolicyError> {
+		Self::new(tract_onnx::onnx().model_for_read(reader)?)
+	}
+
+	fn new(mut model: InferenceModel) -> Result<Self, PolicyError> {
+		let obs_len = model
+			.input_fact(0)?
+			.shape
+			.dims()
+			.nth(1)
+			.and_then(|dim| dim.concretize())
+			.and_then(|dim| dim.to_usize().ok());
+		if let Some(len) = obs_len.filter(|&len| len != OBS_LEN) {
+			return Err(PolicyError::ObsLen(len));
+		}
+
+		let sampled: Vec<_> = model
+			.nodes()
+			.iter()
+			.filter(|node| node.op.name() == "Multinomial")
+			.map(|node| logits(&model, node.inputs[0]))
+			.collect();
+		if !sampled.is_empty() {
+			model.set_output_outlets(&sampled)?;
+		}
+
+		// Whatever batch size the model was exported with, bots only ever need one observation. The
+		// shapes inferred from the exported one are dropped so they get inferred again.
+		let inputs = model.input_outlets()?.to_vec();
+		for id in 0..model.nodes().len() {
+			for slot in 0..model.node(id).outputs.len() {
+				let outlet = OutletId::new(id, slot);
+				if !inputs.contains(&outlet) {
+					model.set_outlet_fact(outlet, InferenceFact::default())?;
+				}
+			}
+		}
+		let plan = model
+			.with_input_fact(0, f32::fact([1, OBS_LEN]).into())?
+			.into_optimized()?
+			.into_runnable()?;
+
+		let actions = run(&plan, &[0.0; OBS_LEN])?.len();
+		if actions != ACTION_COUNT {
+			return Err(PolicyError::Actions(actions));
+		}
+
+		Ok(Policy {
+			plan: Arc::new(plan),
+		})
+	}
+
+	/// Most likely input for `obs`.
+	pub fn input(&self, obs: &Observation) -> Result<FgInput, PolicyError> {
+		let logits = self.logits(obs)?;
+		let action = logits
+			.iter()
+			.enumerate()
+			.max_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map_or(0, |(action, _)| action as u8);
+
+		Ok(env::action_input(action))
+	}
+
+	/// Input for `obs` drawn the way the policy was trained to act.
+	pub fn sample(&self, obs: &Observation, rng: &mut Rng) -> Result<FgInput, PolicyError> {
+		let logits = self.logits(obs)?;
+		let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+		let weights: Vec<_> = logits.iter().map(|logit| (logit - max).exp()).collect();
+
+		// Takes each action with its share of the weight not yet passed over.
+		let mut left: f32 = weights.iter().sum();
+		let action = weights
+			.iter()
+			.position(|&weight| {
+				let taken = rng.chance(weight / left);
+				left -= weight;
+				taken
+			})
+			.unwrap_or(ACTION_COUNT - 1);
+
+		Ok(env::action_input(action as u8))
+	}
+
+	/// One logit per action for `obs`.
+	pub fn logits(&self, obs: &Observation) -> Result<Vec<f32>, PolicyError> {
+		run(&self.plan, obs).map_err(PolicyError::Run)
+	}
+}
+
+/// Walks back from the input of a sampling op to the logits its probabilities come from.
+fn logits(model: &InferenceModel, mut outlet: OutletId) -> OutletId {
+	loop {
+		let node = model.node(outlet.node);
+		match node.op.name().as_ref() {
+			"Softmax" | "LogSoftmax" | "Ln" => outlet = node.inputs[0],
+			_ => return outlet,
+		}
+	}
+}
+
+/// Values of every output, in order.
+fn run(plan: &TypedSimplePlan<TypedModel>, obs: &[f32]) -> TractResult<Vec<f32>> {
+	let input = Tensor::from_shape(&[1, OBS_LEN], obs)?;
+
+	let mut values = Vec::new();
+	for output in plan.run(tvec!(input.into()))? {
+		values.extend_from_slice(output.cast_to::<f32>()?.as_slice::<f32>()?);
+	}
+
+	Ok(values)
+}
+
+#[derive(Debug)]
+pub enum PolicyError {
+	Io(io::Error),
+	Model(TractError),
+	/// The model takes observations of another length.
+	ObsLen(usize),
+	/// The model gives another number of action logits.
+	Actions(usize),
+	/// The model failed on an observation.
+	Run(TractError),
+}
+
+impl fmt::Display for PolicyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PolicyError::Io(err) => write!(f, "could not read policy: {err}"),
+			PolicyError::Model(err) => write!(f, "could not load policy: {err}"),
+			PolicyError::ObsLen(len) => write!(
+				f,
+				"policy takes observations of {len} values, expected {OBS_LEN}"
+			),
+			PolicyError::Actions(count) => write!(
+				f,
+				"policy gives {count} action logits, expected {ACTION_COUNT}"
+			),
+			PolicyError::Run(err) => write!(f, "could not run policy: {err}"),
+		}
+	}
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<io::Error> for PolicyError {
+	fn from(err: io::Error) -> Self {
+		PolicyError::Io(err)
+	}
+}
+
+impl From<TractError> for PolicyError {
+	fn from(err: TractError) -> Self {
+		PolicyError::Model(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use tract_onnx::pb::{
+		tensor_proto::DataType,
+		tensor_shape_proto::{dimension::Value, Dimension},
+		type_proto, GraphProto, ModelProto, NodeProto, OperatorSetIdProto, TensorProto,
+		TensorShapeProto, TypeProto, ValueInfoProto,
+	};
+
+	use super::*;
+	use crate::{
+		bot::{Bot, BotKind, PolicyBot, View},
+		simul::{Match, Result},
+	};
+
+	fn value_info(name: &str, elem_type: DataType, dims: &[i64]) -> ValueInfoProto {
+		let dim = dims
+			.iter()
+			.map(|&dim| Dimension {
+				value: Some(Value::DimValue(dim)),
+				..Default::default()
+			})
+			.collect();
+
+		ValueInfoProto {
+			name: name.to_owned(),
+			r#type: Some(TypeProto {
+				value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+					elem_type: elem_type as i32,
+					shape: Some(TensorShapeProto { dim }),
+				})),
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	fn node(op_type: &str, inputs: &[&str], output: &str) -> NodeProto {
+		NodeProto {
+			op_type: op_type.to_owned(),
+			input: inputs.iter().map(|&input| input.to_owned()).collect(),
+			output: vec![output.to_owned()],
+			name: output.to_owned(),
+			..Default::default()
+		}
+	}
+
+	/// Picks action `7` with a full meter and `4` otherwise, then samples like sample-factory
+	/// exports do.
+	fn model() -> ModelProto {
+		let mut weight = vec![0.0; OBS_LEN * ACTION_COUNT];
+		weight[3 * ACTION_COUNT + 7] = 2.0;
+		let mut bias = vec![0.0; ACTION_COUNT];
+		bias[4] = 1.0;
+
+		let tensor = |name: &str, dims: Vec<i64>, float_data| TensorProto {
+			name: name.to_owned(),
+			dims,
+			data_type: DataType::Float as i32,
+			float_data,
+			..Default::default()
+		};
+
+		ModelProto {
+			ir_version: 8,
+			opset_import: vec![OperatorSetIdProto {
+				domain: String::new(),
+				version: 13,
+			}],
+			graph: Some(GraphProto {
+				node: vec![
+					node("Gemm", &["obs", "weight", "bias"], "logits"),
+					node("Softmax", &["logits"], "probs"),
+					node("Log", &["probs"], "log_probs"),
+					node("Multinomial", &["log_probs"], "actions"),
+				],
+				initializer: vec![
+					tensor(
+						"weight",
+						vec![OBS_LEN as i64, ACTION_COUNT as i64],
+						weight,
+					),
+					tensor("bias", vec![ACTION_COUNT as i64], bias),
+				],
+				input: vec![value_info(
+					"obs",
+					DataType::Float,
+					&[1, OBS_LEN as i64],
+				)],
+				output: vec![value_info("actions", DataType::Int32, &[1, 1])],
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn actions() {
+		let model = tract_onnx::onnx().model_for_proto_model(&model()).unwrap();
+		let policy = Policy::new(model).unwrap();
+
+		let mut obs = [0.0; OBS_LEN];
+		assert_eq!(policy.input(&obs).unwrap(), env::action_input(4));
+		obs[3] = 1.0;
+		assert_eq!(policy.input(&obs).unwrap(), env::action_input(7));
+
+		// Logits of 2 for action 7, 1 for action 4 and 0 otherwise, so 7 gets drawn 43% of the time.
+		let mut rng = Rng::new(0);
+		let sampled = (0..1000)
+			.filter(|_| policy.sample(&obs, &mut rng).unwrap() == env::action_input(7))
+			.count();
+		assert!((380..480).contains(&sampled), "{sampled}");
+	}
+
+	#[test]
+	fn play() {
+		let model = tract_onnx::onnx().model_for_proto_model(&model()).unwrap();
+		let policy = Policy::new(model).unwrap();
+
+		// A whole match against a random bot, played the same way with the same seed.
+		let play = || {
+			let mut bot = PolicyBot::new(policy.clone(), 0);
+			let mut opponent = BotKind::Random.bot(0);
+			let mut sim = Match::new(true, true);
+			let mut inputs = Vec::new();
+
+			loop {
+				let input = bot.input(View::new(&sim, true));
+				let res = sim.step(input, opponent.input(View::new(&sim, false)));
+				inputs.push(input);
+
+				if !matches!(res, Result::Continue | Result::Pause)
+					&& !sim.continues()
+				{
+					break inputs;
+				}
+			}
+		};
+
+		let inputs = play();
+		assert!(inputs.iter().any(|input| input.movement != 0));
+		assert_eq!(play(), inputs);
+	}
+
+	#[test]
+	fn godot_model() {
+		// Trained on observations built by the Godot game, not on those of `Env`.
+		let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../godot/model.onnx");
+		let bytes = std::fs::read(path).unwrap();
+		assert!(matches!(
+			Policy::from_bytes(&bytes),
+			Err(PolicyError::ObsLen(236))
+		));
+	}
+}
@@ -0,0 +1,188 @@
+use numpy::{prelude::*, PyArray1, PyArray2, PyArray3, PyReadonlyArrayDyn};
+use pyo3::{
+	exceptions::PyValueError,
+	prelude::*,
+	types::{PyDict, PyTuple},
+};
+
+use super::simul::{registered_character, Match};
+use crate::{batch, env, simul};
+
+/// Single match environment, see the Rust `env::Env`.
+#[pyclass(module = "footsies_sim")]
+pub struct Env {
+	env: env::Env,
+}
+
+#[pymethods]
+impl Env {
+	#[new]
+	#[pyo3(signature = (p1_char=0, p2_char=0, noop_max=0))]
+	fn new(p1_char: u8, p2_char: u8, noop_max: u16) -> PyResult<Self> {
+		Ok(Env {
+			env: env::Env::new(initial_match(p1_char, p2_char)?)
+				.with_noop_max(noop_max),
+		})
+	}
+
+	/// Returns the `[2, OBS_LEN]` observations of both players.
+	#[pyo3(signature = (seed=0))]
+	fn reset<'py>(
+		&mut self,
+		py: Python<'py>,
+		seed: u64,
+	) -> PyResult<Bound<'py, PyArray2<f32>>> {
+		self.env.reset(seed);
+		self.observations(py)
+	}
+
+	/// Returns `(obs, reward, done, info)`, with `obs` shaped `[2, OBS_LEN]` and `reward` `[2]`.
+	fn step<'py>(
+		&mut self,
+		py: Python<'py>,
+		action_p1: u8,
+		action_p2: u8,
+	) -> PyResult<Bound<'py, PyTuple>> {
+		check_actions(&[action_p1, action_p2])?;
+		let (reward, done, info) = self.env.advance(action_p1, action_p2);
+
+		let dict = PyDict::new(py);
+		dict.set_item("result", info.result)?;
+		dict.set_item("frame", info.frame)?;
+		dict.set_item("p1_wins", info.p1_wins)?;
+		dict.set_item("p2_wins", info.p2_wins)?;
+
+		let obs = self.observations(py)?.into_any();
+		let reward = PyArray1::from_slice(py, &reward).into_any();
+		let done = done.into_pyobject(py)?.to_owned().into_any();
+
+		PyTuple::new(py, [obs, reward, done, dict.into_any()])
+	}
+
+	/// Starts the next round, or a new match once the current one is decided. Returns whether a
+	/// new match was started.
+	fn next_round(&mut self) -> bool {
+		self.env.next_round()
+	}
+
+	fn observations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+		let mut obs = [0.0; 2 * env::OBS_LEN];
+		let (p1, p2) = obs.split_at_mut(env::OBS_LEN);
+		self.env.write_observation(true, p1);
+		self.env.write_observation(false, p2);
+
+		PyArray1::from_slice(py, &obs).reshape([2, env::OBS_LEN])
+	}
+
+	/// Copy of the simulated match.
+	#[getter]
+	fn sim(&self) -> Match {
+		Match {
+			sim: self.env.sim().clone(),
+		}
+	}
+}
+
+/// Lockstep matches, see the Rust `batch::BatchEnv`.
+#[pyclass(module = "footsies_sim")]
+pub struct BatchEnv {
+	env: batch::BatchEnv,
+}
+
+#[pymethods]
+impl BatchEnv {
+	#[new]
+	#[pyo3(signature = (num_envs, p1_char=0, p2_char=0, noop_max=0, parallel=false))]
+	fn new(
+		num_envs: usize,
+		p1_char: u8,
+		p2_char: u8,
+		noop_max: u16,
+		parallel: bool,
+	) -> PyResult<Self> {
+		let env = batch::BatchEnv::new(initial_match(p1_char, p2_char)?, num_envs)
+			.with_noop_max(noop_max);
+
+		#[cfg(feature = "parallel")]
+		let env = env.with_parallel(parallel);
+		#[cfg(not(feature = "parallel"))]
+		if parallel {
+			return Err(PyValueError::new_err(
+				"Built without the `parallel` feature.",
+			));
+		}
+
+		Ok(BatchEnv { env })
+	}
+
+	/// Resets match `i` with `seed + i` and returns the `[num_envs, 2, OBS_LEN]` observations.
+	#[pyo3(signature = (seed=0))]
+	fn reset<'py>(
+		&mut self,
+		py: Python<'py>,
+		seed: u64,
+	) -> PyResult<Bound<'py, PyArray3<f32>>> {
+		self.env.reset(seed);
+		self.observations(py)
+	}
+
+	/// Takes `[num_envs, 2]` actions and returns `(obs, rewards, dones, new_matches)`.
+	fn step<'py>(
+		&mut self,
+		py: Python<'py>,
+		actions: PyReadonlyArrayDyn<'py, u8>,
+	) -> PyResult<Bound<'py, PyTuple>> {
+		let actions = actions.as_slice()?;
+		if actions.len() != self.env.len() * 2 {
+			return Err(PyValueError::new_err(format!(
+				"Expected {} actions, got {}.",
+				self.env.len() * 2,
+				actions.len()
+			)));
+		}
+		check_actions(actions)?;
+
+		let env = &mut self.env;
+		py.detach(|| env.step(actions));
+
+		let obs = self.observations(py)?.into_any();
+		let rewards = PyArray1::from_slice(py, self.env.rewards())
+			.reshape([self.env.len(), 2])?
+			.into_any();
+		let dones = PyArray1::from_slice(py, self.env.dones()).into_any();
+		let new_matches = PyArray1::from_slice(py, self.env.new_matches()).into_any();
+
+		PyTuple::new(py, [obs, rewards, dones, new_matches])
+	}
+
+	fn observations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<f32>>> {
+		PyArray1::from_slice(py, self.env.observations()).reshape([
+			self.env.len(),
+			2,
+			env::OBS_LEN,
+		])
+	}
+
+	fn __len__(&self) -> usize {
+		self.env.len()
+	}
+}
+
+fn initial_match(p1_char: u8, p2_char: u8) -> PyResult<simul::Match> {
+	Ok(simul::Match::with_characters(
+		false,
+		false,
+		registered_character(p1_char)?,
+		registered_character(p2_char)?,
+	))
+}
+
+fn check_actions(actions: &[u8]) -> PyResult<()> {
+	match actions.iter().find(|a| **a as usize >= env::ACTION_COUNT) {
+		Some(action) => Err(PyValueError::new_err(format!(
+			"Action {action} is out of range, expected 0..{}.",
+			env::ACTION_COUNT
+		))),
+		None => Ok(()),
+	}
+}
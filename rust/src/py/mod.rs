@@ -0,0 +1,36 @@
+//! Python bindings, built as the `footsies_sim` extension module. Like the Godot bindings, these
+//! only wrap the headless simulator.
+
+mod env;
+mod simul;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+	character::{self, Character},
+	env::{ACTION_COUNT, OBS_LEN},
+	simul::Result,
+};
+
+/// Registers a character from its RON source and returns its id.
+#[pyfunction]
+fn register_character(ron: &str) -> PyResult<u8> {
+	Character::from_ron(ron)
+		.and_then(character::register_character)
+		.map(|id| id.0)
+		.map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+#[pymodule]
+fn footsies_sim(m: &Bound<'_, PyModule>) -> PyResult<()> {
+	m.add("OBS_LEN", OBS_LEN)?;
+	m.add("ACTION_COUNT", ACTION_COUNT)?;
+	m.add_class::<Result>()?;
+	m.add_class::<simul::FgInput>()?;
+	m.add_class::<simul::Match>()?;
+	m.add_class::<env::Env>()?;
+	m.add_class::<env::BatchEnv>()?;
+	m.add_function(wrap_pyfunction!(register_character, m)?)?;
+
+	Ok(())
+}
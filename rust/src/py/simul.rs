@@ -0,0 +1,232 @@
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+use crate::{character::CharacterId, config::MatchConfig, input, simul};
+
+#[pyclass(module = "footsies_sim", from_py_object)]
+#[derive(Debug, Clone, Copy)]
+pub struct FgInput {
+	pub input: input::FgInput,
+}
+
+#[pymethods]
+impl FgInput {
+	#[new]
+	#[pyo3(signature = (movement=0, attack_press=false, special_press=false))]
+	fn new(movement: i8, attack_press: bool, special_press: bool) -> Self {
+		FgInput {
+			input: input::FgInput::new(movement, attack_press, special_press),
+		}
+	}
+
+	#[getter]
+	fn movement(&self) -> i8 {
+		self.input.movement
+	}
+
+	#[getter]
+	fn attack_press(&self) -> bool {
+		self.input.attack_press
+	}
+
+	#[getter]
+	fn special_press(&self) -> bool {
+		self.input.special_press
+	}
+
+	fn __repr__(&self) -> String {
+		format!(
+			"FgInput(movement={}, attack_press={}, special_press={})",
+			self.input.movement,
+			py_bool(self.input.attack_press),
+			py_bool(self.input.special_press)
+		)
+	}
+}
+
+#[pyclass(module = "footsies_sim", from_py_object)]
+#[derive(Debug, Clone)]
+pub struct Match {
+	pub sim: simul::Match,
+}
+
+#[pymethods]
+impl Match {
+	/// `config` is a `MatchConfig` in RON, missing fields keep their defaults.
+	#[new]
+	#[pyo3(signature = (p1_bot=false, p2_bot=false, p1_char=0, p2_char=0, config=None))]
+	fn new(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: u8,
+		p2_char: u8,
+		config: Option<&str>,
+	) -> PyResult<Self> {
+		let config = match config {
+			Some(ron) => MatchConfig::from_ron(ron)
+				.map_err(|err| PyValueError::new_err(err.to_string()))?,
+			None => MatchConfig::DEFAULT,
+		};
+
+		Ok(Match {
+			sim: simul::Match::with_config(
+				p1_bot,
+				p2_bot,
+				registered_character(p1_char)?,
+				registered_character(p2_char)?,
+				config,
+			),
+		})
+	}
+
+	fn config(&self) -> String {
+		self.sim.config().to_ron()
+	}
+
+	fn frame_update(&mut self, input1: FgInput, input2: FgInput) -> simul::Result {
+		self.sim.frame_update(input1.input, input2.input)
+	}
+
+	fn new_round(&mut self) {
+		self.sim.new_round();
+	}
+
+	fn continues(&self) -> bool {
+		self.sim.continues()
+	}
+
+	fn p1_pos(&self) -> i16 {
+		self.sim.p1_pos()
+	}
+
+	fn p2_pos(&self) -> i16 {
+		self.sim.p2_pos()
+	}
+
+	fn player_relative_pos(&self, p1: bool) -> i16 {
+		self.sim.player_relative_pos(p1)
+	}
+
+	fn player_distance(&self) -> i16 {
+		self.sim.player_distance()
+	}
+
+	fn p1_wins(&self) -> u8 {
+		self.sim.p1_wins()
+	}
+
+	fn p2_wins(&self) -> u8 {
+		self.sim.p2_wins()
+	}
+
+	fn p1_meter(&self) -> u16 {
+		self.sim.p1_meter()
+	}
+
+	fn p2_meter(&self) -> u16 {
+		self.sim.p2_meter()
+	}
+
+	fn p1_sprite(&self) -> &'static str {
+		self.sim.p1_sprite()
+	}
+
+	fn p2_sprite(&self) -> &'static str {
+		self.sim.p2_sprite()
+	}
+
+	fn player_state(&self, p1: bool) -> i64 {
+		self.sim.player_state(p1)
+	}
+
+	fn player_state_len(&self, p1: bool) -> i64 {
+		self.sim.player_state_len(p1)
+	}
+
+	fn player_dead(&self, p1: bool) -> bool {
+		self.sim.player_dead(p1)
+	}
+
+	fn player_character(&self, p1: bool) -> u8 {
+		self.sim.player_character(p1).0
+	}
+
+	fn guard_left(&self, p1: bool) -> u8 {
+		self.sim.guard_left(p1)
+	}
+
+	fn health_left(&self, p1: bool) -> u16 {
+		self.sim.health_left(p1)
+	}
+
+	fn timer_sec(&self) -> u16 {
+		self.sim.timer_sec()
+	}
+
+	fn state(&self) -> i64 {
+		self.sim.state()
+	}
+
+	fn state_len(&self) -> i64 {
+		self.sim.state_len()
+	}
+
+	/// `"<player>:<cue>"`, like `"1:nnormal"`.
+	fn audio(&self) -> Vec<String> {
+		self.sim.audio().iter().map(ToString::to_string).collect()
+	}
+
+	fn stage_len(&self) -> i16 {
+		self.sim.stage_len()
+	}
+
+	fn serialize_bin<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+		PyBytes::new(py, &self.sim.serialize_bin())
+	}
+
+	fn deserialize_bin(&mut self, from: &[u8]) -> PyResult<()> {
+		self.sim.deserialize_bin(from).map_err(state_error)
+	}
+
+	fn checksum(&self) -> u64 {
+		self.sim.checksum()
+	}
+
+	fn serialize_ron(&self) -> String {
+		self.sim.serialize_ron()
+	}
+
+	fn deserialize_ron(&mut self, from: &str) -> PyResult<()> {
+		self.sim.deserialize_ron(from).map_err(state_error)
+	}
+
+	fn __copy__(&self) -> Self {
+		self.clone()
+	}
+
+	fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+		self.clone()
+	}
+}
+
+pub fn registered_character(id: u8) -> PyResult<CharacterId> {
+	let id = CharacterId(id);
+
+	match id.is_registered() {
+		true => Ok(id),
+		false => Err(PyValueError::new_err(format!(
+			"Character {} is not registered.",
+			id.0
+		))),
+	}
+}
+
+const fn py_bool(val: bool) -> &'static str {
+	match val {
+		true => "True",
+		false => "False",
+	}
+}
+
+fn state_error(err: simul::StateError) -> PyErr {
+	PyValueError::new_err(err.to_string())
+}
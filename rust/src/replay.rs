@@ -0,0 +1,547 @@
+//! Recorded matches, stored as the initial state plus the inputs of every frame and replayed
+//! through [`Match::step`].
+//!
+//! The binary form starts with [`Replay::MAGIC`] and a little endian `u16` version, followed by the
+//! bincode encoded replay with both inputs of a frame packed into a single byte. The RON form is
+//! the same data spelled out for debugging.
+
+use std::{fmt, iter::Peekable, slice};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	input::FgInput,
+	simul::{Match, Result, StateError},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+	initial: Match,
+	inputs: Vec<[FgInput; 2]>,
+	/// Frames that ended a round, and how.
+	results: Vec<(u32, Result)>,
+	/// [`Match::checksum`] after the last frame.
+	checksum: u64,
+}
+
+impl Replay {
+	pub const MAGIC: [u8; 4] = *b"FSRP";
+	pub const VERSION: u16 = 1;
+
+	pub fn to_bin(&self) -> Vec<u8> {
+		use bincode::{config, serde};
+
+		let packed = PackedReplay {
+			initial: self.initial.clone(),
+			inputs: self
+				.inputs
+				.iter()
+				.map(|&[i1, i2]| pack(i1) | pack(i2) << 4)
+				.collect(),
+			results: self.results.clone(),
+			checksum: self.checksum,
+		};
+
+		let mut bin = Vec::from(Self::MAGIC);
+		bin.extend(Self::VERSION.to_le_bytes());
+		serde::encode_into_std_write(packed, &mut bin, config::standard())
+			.expect("Could not serialize `Replay`.");
+
+		bin
+	}
+
+	pub fn from_bin(from: &[u8]) -> std::result::Result<Self, ReplayError> {
+		use bincode::{config, serde};
+
+		let Some((magic, from)) = from.split_first_chunk::<4>() else {
+			return Err(ReplayError::BadMagic);
+		};
+		if *magic != Self::MAGIC {
+			return Err(ReplayError::BadMagic);
+		}
+
+		let Some((version, from)) = from.split_first_chunk::<2>() else {
+			return Err(ReplayError::BadMagic);
+		};
+		check_version(u16::from_le_bytes(*version))?;
+
+		let (packed, len): (PackedReplay, _) =
+			serde::decode_from_slice(from, config::standard())?;
+		if len != from.len() {
+			return Err(ReplayError::TrailingBytes);
+		}
+		packed.initial.check_state()?;
+		let inputs = packed
+			.inputs
+			.iter()
+			.map(|&byte| Some([unpack(byte)?, unpack(byte >> 4)?]))
+			.collect::<Option<_>>()
+			.ok_or(ReplayError::Corrupt)?;
+
+		Ok(Replay {
+			initial: packed.initial,
+			inputs,
+			results: packed.results,
+			checksum: packed.checksum,
+		})
+	}
+
+	pub fn to_ron(&self) -> String {
+		let versioned = Versioned {
+			version: Self::VERSION,
+			replay: self,
+		};
+
+		ron::ser::to_string_pretty(&versioned, ron::ser::PrettyConfig::new())
+			.expect("Could not serialize `Replay`.")
+	}
+
+	pub fn from_ron(from: &str) -> std::result::Result<Self, ReplayError> {
+		let versioned: Versioned<Self> = ron::from_str(from)?;
+		check_version(versioned.version)?;
+		versioned.replay.initial.check_state()?;
+
+		Ok(versioned.replay)
+	}
+
+	/// Replays the whole match and checks that every round ends on the recorded frame with the
+	/// recorded result, and that the final state is the recorded one.
+	pub fn verify(&self) -> std::result::Result<(), VerifyError> {
+		let mut sim = self.initial.clone();
+		let mut recorded = self.results.iter().peekable();
+
+		for (frame, &[input1, input2]) in (0..).zip(&self.inputs) {
+			let replayed = round_result(sim.step(input1, input2));
+			let expected = next_result(&mut recorded, frame);
+
+			if replayed != expected {
+				return Err(VerifyError::Result {
+					frame,
+					recorded: expected,
+					replayed,
+				});
+			}
+		}
+
+		match sim.checksum() {
+			checksum if checksum == self.checksum => Ok(()),
+			checksum => Err(VerifyError::Checksum {
+				recorded: self.checksum,
+				replayed: checksum,
+			}),
+		}
+	}
+
+	#[inline]
+	pub fn initial(&self) -> &Match {
+		&self.initial
+	}
+
+	#[inline]
+	pub fn inputs(&self) -> &[[FgInput; 2]] {
+		&self.inputs
+	}
+
+	#[inline]
+	pub fn results(&self) -> &[(u32, Result)] {
+		&self.results
+	}
+
+	/// Number of recorded frames.
+	#[inline]
+	pub fn len(&self) -> u32 {
+		self.inputs.len() as u32
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.inputs.is_empty()
+	}
+}
+
+/// Builds a [`Replay`] one frame at a time, alongside the game loop.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+	replay: Replay,
+}
+
+impl Recorder {
+	pub fn new(initial: &Match) -> Self {
+		Recorder {
+			replay: Replay {
+				initial: initial.clone(),
+				inputs: Vec::new(),
+				results: Vec::new(),
+				checksum: initial.checksum(),
+			},
+		}
+	}
+
+	/// Records a frame the caller already ran with [`Match::step`]. Only the sign of `movement` is
+	/// kept, which is all the game ever sends.
+	pub fn record(&mut self, input1: FgInput, input2: FgInput, result: Result) {
+		let frame = self.replay.len();
+
+		if let Some(result) = round_result(result) {
+			self.replay.results.push((frame, result));
+		}
+		self.replay
+			.inputs
+			.push([normalize(input1), normalize(input2)]);
+	}
+
+	/// Runs a frame of `sim` and records it.
+	pub fn step(&mut self, sim: &mut Match, input1: FgInput, input2: FgInput) -> Result {
+		let res = sim.step(input1, input2);
+		self.record(input1, input2, res);
+
+		res
+	}
+
+	/// `last` is the state after the last recorded frame.
+	pub fn finish(mut self, last: &Match) -> Replay {
+		self.replay.checksum = last.checksum();
+
+		self.replay
+	}
+
+	/// Number of recorded frames.
+	#[inline]
+	pub fn len(&self) -> u32 {
+		self.replay.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.replay.is_empty()
+	}
+}
+
+/// Plays a [`Replay`] back, seeking by simulating from the closest keyframe. Keyframes are saved
+/// every `keyframe_interval` frames the first time playback passes them.
+#[derive(Debug, Clone)]
+pub struct ReplayPlayer {
+	replay: Replay,
+	sim: Match,
+	/// Next frame to be simulated.
+	frame: u32,
+	keyframes: Vec<Match>,
+	keyframe_interval: u32,
+}
+
+impl ReplayPlayer {
+	pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 600;
+
+	pub fn new(replay: Replay) -> Self {
+		Self::with_keyframe_interval(replay, Self::DEFAULT_KEYFRAME_INTERVAL)
+	}
+
+	pub fn with_keyframe_interval(replay: Replay, keyframe_interval: u32) -> Self {
+		ReplayPlayer {
+			sim: replay.initial.clone(),
+			keyframes: vec![replay.initial.clone()],
+			replay,
+			frame: 0,
+			keyframe_interval: keyframe_interval.max(1),
+		}
+	}
+
+	/// Runs the next frame. Returns `None` once the replay is over.
+	pub fn step(&mut self) -> Option<Result> {
+		let [input1, input2] = *self.replay.inputs.get(self.frame as usize)?;
+		let res = self.sim.step(input1, input2);
+		self.frame += 1;
+
+		if self.frame.is_multiple_of(self.keyframe_interval)
+			&& self.keyframes.len() as u32 == self.frame / self.keyframe_interval
+		{
+			self.keyframes.push(self.sim.clone());
+		}
+
+		Some(res)
+	}
+
+	/// Moves to the state before `frame`, clamped to the end of the replay.
+	pub fn seek(&mut self, frame: u32) {
+		let frame = frame.min(self.replay.len());
+		let keyframe =
+			(frame / self.keyframe_interval).min(self.keyframes.len() as u32 - 1);
+		let keyframe_frame = keyframe * self.keyframe_interval;
+
+		if frame < self.frame || keyframe_frame > self.frame {
+			self.sim = self.keyframes[keyframe as usize].clone();
+			self.frame = keyframe_frame;
+		}
+
+		while self.frame < frame {
+			self.step();
+		}
+	}
+
+	#[inline]
+	pub fn sim(&self) -> &Match {
+		&self.sim
+	}
+
+	/// Next frame to be simulated.
+	#[inline]
+	pub fn frame(&self) -> u32 {
+		self.frame
+	}
+
+	#[inline]
+	pub fn replay(&self) -> &Replay {
+		&self.replay
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+	/// A round ended differently, or on a different frame. `None` is a frame that did not end a
+	/// round.
+	Result {
+		frame: u32,
+		recorded: Option<Result>,
+		replayed: Option<Result>,
+	},
+	/// Every round ended as recorded, but the final state differs.
+	Checksum { recorded: u64, replayed: u64 },
+}
+
+impl fmt::Display for VerifyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VerifyError::Result {
+				frame,
+				recorded,
+				replayed,
+			} => write!(
+				f,
+				"frame {frame} recorded round result {recorded:?}, replayed {replayed:?}"
+			),
+			VerifyError::Checksum { recorded, replayed } => write!(
+				f,
+				"final state checksum {replayed:#018x} does not match recorded {recorded:#018x}"
+			),
+		}
+	}
+}
+
+impl std::error::Error for VerifyError {}
+
+#[derive(Debug)]
+pub enum ReplayError {
+	BadMagic,
+	UnsupportedVersion(u16),
+	Decode(bincode::error::DecodeError),
+	Parse(ron::error::SpannedError),
+	TrailingBytes,
+	/// Decoded, but holds inputs no recorder writes.
+	Corrupt,
+	/// Decoded, but starts from a state [`Match::from_bin`] would reject.
+	InvalidState(StateError),
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReplayError::BadMagic => write!(f, "not a replay file"),
+			ReplayError::UnsupportedVersion(version) => write!(
+				f,
+				"replay version {version} is not supported, expected {}",
+				Replay::VERSION
+			),
+			ReplayError::Decode(err) => write!(f, "could not decode replay: {err}"),
+			ReplayError::Parse(err) => write!(f, "could not parse replay: {err}"),
+			ReplayError::TrailingBytes => write!(f, "replay has trailing bytes"),
+			ReplayError::Corrupt => write!(f, "replay holds invalid inputs"),
+			ReplayError::InvalidState(err) => {
+				write!(f, "replay starts from an invalid state: {err}")
+			}
+		}
+	}
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<bincode::error::DecodeError> for ReplayError {
+	fn from(err: bincode::error::DecodeError) -> Self {
+		ReplayError::Decode(err)
+	}
+}
+
+impl From<StateError> for ReplayError {
+	fn from(err: StateError) -> Self {
+		ReplayError::InvalidState(err)
+	}
+}
+
+impl From<ron::error::SpannedError> for ReplayError {
+	fn from(err: ron::error::SpannedError) -> Self {
+		ReplayError::Parse(err)
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct PackedReplay {
+	initial: Match,
+	inputs: Vec<u8>,
+	results: Vec<(u32, Result)>,
+	checksum: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Versioned<R> {
+	version: u16,
+	replay: R,
+}
+
+fn check_version(version: u16) -> std::result::Result<(), ReplayError> {
+	match version {
+		Replay::VERSION => Ok(()),
+		version => Err(ReplayError::UnsupportedVersion(version)),
+	}
+}
+
+#[inline]
+fn round_result(res: Result) -> Option<Result> {
+	match res {
+		Result::Continue | Result::Pause => None,
+		res => Some(res),
+	}
+}
+
+fn next_result(recorded: &mut Peekable<slice::Iter<(u32, Result)>>, frame: u32) -> Option<Result> {
+	recorded.next_if(|(f, _)| *f == frame).map(|&(_, res)| res)
+}
+
+#[inline]
+const fn normalize(input: FgInput) -> FgInput {
+	FgInput::new(
+		input.movement.signum(),
+		input.attack_press,
+		input.special_press,
+	)
+}
+
+/// Movement in the low two bits, then attack and special.
+#[inline]
+const fn pack(input: FgInput) -> u8 {
+	(input.movement.signum() + 1) as u8
+		| (input.attack_press as u8) << 2
+		| (input.special_press as u8) << 3
+}
+
+#[inline]
+const fn unpack(byte: u8) -> Option<FgInput> {
+	match byte & 0b11 {
+		0b11 => None,
+		movement => Some(FgInput::new(
+			movement as i8 - 1,
+			byte & 0b100 != 0,
+			byte & 0b1000 != 0,
+		)),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{env::action_input, rng::Rng};
+
+	fn record(frames: u32, seed: u64) -> Replay {
+		let mut rng = Rng::new(seed);
+		let mut sim = Match::new(false, false);
+		let mut recorder = Recorder::new(&sim);
+
+		for _ in 0..frames {
+			let input1 = action_input(rng.below(9) as u8);
+			let input2 = action_input(rng.below(9) as u8);
+			recorder.step(&mut sim, input1, input2);
+		}
+
+		recorder.finish(&sim)
+	}
+
+	#[test]
+	fn roundtrip() {
+		let replay = record(2000, 5);
+		assert!(!replay.results().is_empty());
+		assert_eq!(replay.verify(), Ok(()));
+
+		let bin = replay.to_bin();
+		// Initial state and one byte per frame.
+		assert!(bin.len() < 2000 + 200);
+		assert_eq!(Replay::from_bin(&bin).unwrap().to_bin(), bin);
+		assert_eq!(Replay::from_ron(&replay.to_ron()).unwrap().to_bin(), bin);
+
+		let mut bad = bin.clone();
+		bad[4] = 0xff;
+		assert!(matches!(
+			Replay::from_bin(&bad),
+			Err(ReplayError::UnsupportedVersion(_))
+		));
+		assert!(matches!(
+			Replay::from_bin(b"nope"),
+			Err(ReplayError::BadMagic)
+		));
+
+		let mut trailing = bin.clone();
+		trailing.push(0);
+		assert!(matches!(
+			Replay::from_bin(&trailing),
+			Err(ReplayError::TrailingBytes)
+		));
+	}
+
+	#[test]
+	fn invalid_state() {
+		let ron = record(100, 8).to_ron();
+		assert!(ron.contains("wins_needed: 3"));
+
+		let invalid = ron.replace("wins_needed: 3", "wins_needed: 0");
+		assert!(matches!(
+			Replay::from_ron(&invalid),
+			Err(ReplayError::InvalidState(StateError::InvalidConfig(_)))
+		));
+	}
+
+	#[test]
+	fn verify() {
+		let mut replay = record(2000, 6);
+		let (frame, _) = replay.results[0];
+
+		// Nobody attacks, so the first round can not end where it was recorded.
+		replay.inputs[..=frame as usize].fill([FgInput::default(); 2]);
+		assert!(matches!(
+			replay.verify(),
+			Err(VerifyError::Result { frame: f, replayed: None, .. }) if f == frame
+		));
+	}
+
+	#[test]
+	fn seek() {
+		let replay = record(1000, 7);
+		let mut expected = Vec::new();
+		let mut player = ReplayPlayer::with_keyframe_interval(replay.clone(), 64);
+		while player.step().is_some() {
+			expected.push(player.sim().checksum());
+		}
+
+		let mut player = ReplayPlayer::with_keyframe_interval(replay, 64);
+		for frame in [500, 10, 999, 300, 301, 0, 1000] {
+			player.seek(frame);
+			assert_eq!(player.frame(), frame);
+
+			match frame.checked_sub(1) {
+				Some(prev) => {
+					assert_eq!(player.sim().checksum(), expected[prev as usize])
+				}
+				None => assert_eq!(
+					player.sim().checksum(),
+					player.replay().initial().checksum()
+				),
+			}
+		}
+	}
+}
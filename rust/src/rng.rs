@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// Small deterministic random number generator (SplitMix64). It is part of the serialized state of
+/// anything that uses it, so the same seed always gives the same match on every platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	pub const fn new(seed: u64) -> Self {
+		Rng { state: seed }
+	}
+
+	pub const fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	/// Uniform in `0..bound`. `bound` must not be zero.
+	pub const fn below(&mut self, bound: u32) -> u32 {
+		(((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+	}
+
+	/// `true` with the given probability.
+	pub fn chance(&mut self, probability: f32) -> bool {
+		((self.next_u64() >> 40) as f32 / (1u64 << 24) as f32) < probability
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Rng;
+
+	#[test]
+	fn deterministic() {
+		let mut a = Rng::new(7);
+		let mut b = Rng::new(7);
+		let mut c = Rng::new(8);
+
+		for _ in 0..100 {
+			let x = a.next_u64();
+			assert_eq!(x, b.next_u64());
+			assert_ne!(x, c.next_u64());
+		}
+	}
+
+	#[test]
+	fn below() {
+		let mut rng = Rng::new(0);
+		let mut seen = [false; 9];
+
+		for _ in 0..1000 {
+			let x = rng.below(9);
+			assert!(x < 9);
+			seen[x as usize] = true;
+		}
+
+		assert!(seen.iter().all(|s| *s));
+		assert!(!rng.chance(0.0));
+		assert!(rng.chance(1.0));
+	}
+}
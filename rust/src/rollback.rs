@@ -0,0 +1,380 @@
+//! GGPO style rollback over [`Match`].
+//!
+//! Inputs of both players are added with [`RollbackSession::add_input`] as they get confirmed, the
+//! local one usually a few frames ahead as input delay and the remote one late. Frames without a
+//! confirmed input are simulated with a prediction, and once the real input turns out to be
+//! different the session loads the state saved before that frame and simulates again up to the
+//! present.
+
+use std::{collections::VecDeque, fmt};
+
+use crate::{
+	input::FgInput,
+	simul::{Match, Result},
+};
+
+#[derive(Debug, Clone)]
+pub struct RollbackSession {
+	sim: Match,
+	/// Next frame to be simulated.
+	frame: u32,
+	inputs: [InputQueue; 2],
+	/// Ring buffer of the states before each of the last `max_prediction + 1` frames, and the
+	/// inputs those frames were simulated with.
+	saved: Vec<Option<SavedFrame>>,
+	max_prediction: u32,
+	/// Earliest frame simulated with a wrong prediction.
+	rollback_to: Option<u32>,
+}
+
+impl RollbackSession {
+	/// `max_prediction` is how many frames the session can simulate with predicted inputs. At `0`
+	/// it only simulates confirmed frames and never rolls back.
+	pub fn new(sim: Match, max_prediction: u32) -> Self {
+		RollbackSession {
+			sim,
+			frame: 0,
+			inputs: [InputQueue::new(), InputQueue::new()],
+			saved: vec![None; max_prediction as usize + 1],
+			max_prediction,
+			rollback_to: None,
+		}
+	}
+
+	/// Confirms the input of a player for a frame. Inputs of a player have to be added in order
+	/// without gaps, starting from frame `0`.
+	pub fn add_input(
+		&mut self,
+		p1: bool,
+		frame: u32,
+		input: FgInput,
+	) -> std::result::Result<(), RollbackError> {
+		let queue = &mut self.inputs[player_index(p1)];
+
+		if frame != queue.next_frame() {
+			return Err(RollbackError::InputOutOfOrder {
+				expected: queue.next_frame(),
+				got: frame,
+			});
+		}
+		queue.push(input);
+
+		if frame < self.frame {
+			let saved = self
+				.saved_frame(frame)
+				.expect("Frame is within prediction range.");
+
+			if saved.inputs[player_index(p1)] != input {
+				self.rollback_to =
+					Some(self.rollback_to.map_or(frame, |f| f.min(frame)));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Simulates the next frame, after correcting any earlier misprediction. Fails without
+	/// simulating if the session is already `max_prediction` frames ahead of a player's inputs.
+	pub fn advance_frame(&mut self) -> std::result::Result<Result, RollbackError> {
+		if !self.can_advance() {
+			return Err(RollbackError::PredictionThreshold);
+		}
+
+		self.synchronize();
+
+		Ok(self.simulate_frame())
+	}
+
+	/// Rolls back and simulates again if a confirmed input contradicts its prediction. Returns the
+	/// number of frames simulated again.
+	pub fn synchronize(&mut self) -> u32 {
+		let Some(from) = self.rollback_to.take() else {
+			return 0;
+		};
+
+		let now = self.frame;
+		self.sim = self
+			.saved_frame(from)
+			.expect("Frame is within prediction range.")
+			.state
+			.clone();
+		self.frame = from;
+
+		while self.frame < now {
+			self.simulate_frame();
+		}
+
+		now - from
+	}
+
+	fn simulate_frame(&mut self) -> Result {
+		let frame = self.frame;
+		let inputs = [self.inputs[0].get(frame), self.inputs[1].get(frame)];
+
+		let slot = frame as usize % self.saved.len();
+		self.saved[slot] = Some(SavedFrame {
+			frame,
+			state: self.sim.clone(),
+			inputs,
+		});
+
+		// Stepping into the next round keeps the round transition inside what gets rolled back.
+		let res = self.sim.step(inputs[0], inputs[1]);
+		self.frame += 1;
+
+		// Inputs before the oldest saved state can not cause a rollback anymore, but the last
+		// confirmed one is kept for predictions.
+		let oldest = self.frame.saturating_sub(self.saved.len() as u32);
+		self.inputs[0].discard_before(oldest);
+		self.inputs[1].discard_before(oldest);
+
+		res
+	}
+
+	fn saved_frame(&self, frame: u32) -> Option<&SavedFrame> {
+		self.saved[frame as usize % self.saved.len()]
+			.as_ref()
+			.filter(|saved| saved.frame == frame)
+	}
+
+	/// Current state, with predictions for the frames not confirmed yet.
+	#[inline]
+	pub fn sim(&self) -> &Match {
+		&self.sim
+	}
+
+	/// Next frame to be simulated.
+	#[inline]
+	pub fn frame(&self) -> u32 {
+		self.frame
+	}
+
+	/// First frame that is missing an input from at least one player. Everything before it is
+	/// final.
+	#[inline]
+	pub fn confirmed_frame(&self) -> u32 {
+		self.inputs[0].next_frame().min(self.inputs[1].next_frame())
+	}
+
+	#[inline]
+	pub fn max_prediction(&self) -> u32 {
+		self.max_prediction
+	}
+
+	/// Whether [`RollbackSession::advance_frame`] simulates, the next frame leaving at most
+	/// `max_prediction` frames simulated past the confirmed inputs. Inputs may be confirmed past
+	/// the current frame.
+	#[inline]
+	pub fn can_advance(&self) -> bool {
+		(self.frame + 1).saturating_sub(self.confirmed_frame()) <= self.max_prediction
+	}
+}
+
+#[derive(Debug, Clone)]
+struct SavedFrame {
+	frame: u32,
+	state: Match,
+	inputs: [FgInput; 2],
+}
+
+/// Confirmed inputs of a player, `inputs[i]` being the input of frame `first + i`.
+#[derive(Debug, Clone)]
+struct InputQueue {
+	first: u32,
+	inputs: VecDeque<FgInput>,
+}
+
+impl InputQueue {
+	const fn new() -> Self {
+		InputQueue {
+			first: 0,
+			inputs: VecDeque::new(),
+		}
+	}
+
+	#[inline]
+	fn next_frame(&self) -> u32 {
+		self.first + self.inputs.len() as u32
+	}
+
+	#[inline]
+	fn push(&mut self, input: FgInput) {
+		self.inputs.push_back(input);
+	}
+
+	/// The confirmed input, or a prediction that keeps the last movement without any presses.
+	fn get(&self, frame: u32) -> FgInput {
+		if frame < self.next_frame() {
+			return self.inputs[(frame - self.first) as usize];
+		}
+
+		match self.inputs.back() {
+			Some(last) => FgInput::new(last.movement, false, false),
+			None => FgInput::default(),
+		}
+	}
+
+	fn discard_before(&mut self, frame: u32) {
+		while self.first < frame && self.inputs.len() > 1 {
+			self.inputs.pop_front();
+			self.first += 1;
+		}
+	}
+}
+
+#[inline]
+const fn player_index(p1: bool) -> usize {
+	match p1 {
+		true => 0,
+		false => 1,
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollbackError {
+	/// Inputs have to be added in order, without skipping frames.
+	InputOutOfOrder { expected: u32, got: u32 },
+	/// Too far ahead of the confirmed inputs, wait for the remote player before advancing.
+	PredictionThreshold,
+}
+
+impl fmt::Display for RollbackError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RollbackError::InputOutOfOrder { expected, got } => {
+				write!(f, "expected input for frame {expected}, got frame {got}")
+			}
+			RollbackError::PredictionThreshold => {
+				write!(f, "too many frames ahead of the confirmed inputs")
+			}
+		}
+	}
+}
+
+impl std::error::Error for RollbackError {}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{env::action_input, rng::Rng};
+
+	fn random_inputs(len: usize, seed: u64) -> Vec<[FgInput; 2]> {
+		let mut rng = Rng::new(seed);
+
+		(0..len).map(|_| {
+			[
+				action_input(rng.below(9) as u8),
+				action_input(rng.below(9) as u8),
+			]
+		})
+		.collect()
+	}
+
+	#[test]
+	fn matches_direct_simulation() {
+		const FRAMES: usize = 3000;
+		const REMOTE_DELAY: usize = 6;
+		let inputs = random_inputs(FRAMES + REMOTE_DELAY, 11);
+
+		let mut direct = Match::new(false, false);
+		let mut session = RollbackSession::new(Match::new(false, false), 8);
+		let mut rollbacks = 0;
+
+		for frame in 0..FRAMES {
+			// Local input is known right away, the remote one arrives `REMOTE_DELAY` frames late.
+			session.add_input(true, frame as u32, inputs[frame][0])
+				.unwrap();
+			if let Some(remote) = frame.checked_sub(REMOTE_DELAY) {
+				session.add_input(false, remote as u32, inputs[remote][1])
+					.unwrap();
+			}
+
+			rollbacks += session.synchronize();
+			session.advance_frame().unwrap();
+
+			direct.step(inputs[frame][0], inputs[frame][1]);
+		}
+
+		for (remote, input) in inputs
+			.iter()
+			.enumerate()
+			.take(FRAMES)
+			.skip(FRAMES - REMOTE_DELAY)
+		{
+			session.add_input(false, remote as u32, input[1]).unwrap();
+		}
+		session.synchronize();
+
+		assert!(rollbacks > 0);
+		assert_eq!(session.frame(), FRAMES as u32);
+		assert_eq!(session.confirmed_frame(), FRAMES as u32);
+		assert_eq!(session.sim().serialize_bin(), direct.serialize_bin());
+	}
+
+	#[test]
+	fn prediction_threshold() {
+		let mut session = RollbackSession::new(Match::new(false, false), 4);
+
+		for frame in 0..4 {
+			session.add_input(true, frame, FgInput::default()).unwrap();
+			session.advance_frame().unwrap();
+		}
+
+		session.add_input(true, 4, FgInput::default()).unwrap();
+		assert_eq!(
+			session.advance_frame(),
+			Err(RollbackError::PredictionThreshold)
+		);
+
+		session.add_input(false, 0, FgInput::default()).unwrap();
+		assert!(session.advance_frame().is_ok());
+	}
+
+	#[test]
+	fn no_prediction() {
+		let mut session = RollbackSession::new(Match::new(false, false), 0);
+
+		session.add_input(true, 0, FgInput::default()).unwrap();
+		assert_eq!(
+			session.advance_frame(),
+			Err(RollbackError::PredictionThreshold)
+		);
+
+		session.add_input(false, 0, FgInput::default()).unwrap();
+		assert!(session.advance_frame().is_ok());
+		assert!(!session.can_advance());
+	}
+
+	#[test]
+	fn zero_delay() {
+		let inputs = random_inputs(120, 5);
+		let mut direct = Match::new(false, false);
+		let mut session = RollbackSession::new(Match::new(false, false), 4);
+
+		// Both inputs of a frame are confirmed before it is simulated.
+		for (frame, &[input1, input2]) in inputs.iter().enumerate() {
+			session.add_input(true, frame as u32, input1).unwrap();
+			session.add_input(false, frame as u32, input2).unwrap();
+			assert!(session.can_advance());
+			session.advance_frame().unwrap();
+
+			direct.step(input1, input2);
+		}
+
+		assert_eq!(session.sim().serialize_bin(), direct.serialize_bin());
+	}
+
+	#[test]
+	fn out_of_order() {
+		let mut session = RollbackSession::new(Match::new(false, false), 4);
+
+		session.add_input(true, 0, FgInput::default()).unwrap();
+		assert_eq!(
+			session.add_input(true, 2, FgInput::default()),
+			Err(RollbackError::InputOutOfOrder {
+				expected: 1,
+				got: 2
+			})
+		);
+	}
+}
@@ -0,0 +1,180 @@
+//! Scripted inputs for headless matches. Every line of a script holds an optional frame count and
+//! the input to hold for that many frames, `1` when left out:
+//!
+//! ```text
+//! # Walk in, then throw a neutral normal.
+//! 30 >
+//! A
+//! 20 .
+//! ```
+//!
+//! `>` and `<` are forward and back, `A` and `S` attack and special, and `.` holds nothing. Inputs
+//! combine, `>A` is a move normal. Everything after a `#` is a comment. Scripts start over once
+//! they run out.
+
+use std::{fmt, str::FromStr};
+
+use crate::input::FgInput;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputScript {
+	/// Inputs and the frame their run ends on.
+	runs: Vec<(u32, FgInput)>,
+}
+
+impl InputScript {
+	pub fn parse(text: &str) -> Result<Self, ScriptError> {
+		let mut runs = Vec::new();
+		let mut end = 0u32;
+
+		for (index, line) in text.lines().enumerate() {
+			let line_no = index + 1;
+			let line = line.split('#').next().unwrap_or_default().trim();
+			if line.is_empty() {
+				continue;
+			}
+
+			let (count, input) = match line.split_once(char::is_whitespace) {
+				Some((count, input)) => {
+					let count = count.parse::<u32>().map_err(|_| {
+						ScriptError::BadCount { line: line_no }
+					})?;
+					(count, input.trim())
+				}
+				None => (1, line),
+			};
+			let input = parse_input(input).map_err(|symbol| ScriptError::BadInput {
+				line: line_no,
+				symbol,
+			})?;
+
+			if count > 0 {
+				end = end
+					.checked_add(count)
+					.ok_or(ScriptError::BadCount { line: line_no })?;
+				runs.push((end, input));
+			}
+		}
+
+		if runs.is_empty() {
+			return Err(ScriptError::Empty);
+		}
+
+		Ok(InputScript { runs })
+	}
+
+	/// Input for `frame`, counted from the start of the script.
+	pub fn input(&self, frame: u32) -> FgInput {
+		let frame = frame % self.len();
+		let index = self.runs.partition_point(|&(end, _)| end <= frame);
+
+		self.runs[index].1
+	}
+
+	/// Frames before the script starts over, never zero.
+	#[inline]
+	pub fn len(&self) -> u32 {
+		self.runs.last().map_or(0, |&(end, _)| end)
+	}
+
+	/// Always `false`, parsing refuses scripts without inputs.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		false
+	}
+}
+
+impl FromStr for InputScript {
+	type Err = ScriptError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::parse(s)
+	}
+}
+
+/// Input of a line, or the symbol it does not know.
+fn parse_input(text: &str) -> Result<FgInput, char> {
+	let mut input = FgInput::default();
+
+	for symbol in text.chars() {
+		match symbol {
+			'.' => (),
+			'>' if input.movement == 0 => input.movement = 1,
+			'<' if input.movement == 0 => input.movement = -1,
+			'A' => input.attack_press = true,
+			'S' => input.special_press = true,
+			_ => return Err(symbol),
+		}
+	}
+
+	Ok(input)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptError {
+	/// The frame count is not a number, or the script got too long.
+	BadCount {
+		line: usize,
+	},
+	/// Unknown symbol, or a second direction.
+	BadInput {
+		line: usize,
+		symbol: char,
+	},
+	Empty,
+}
+
+impl fmt::Display for ScriptError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ScriptError::BadCount { line } => write!(f, "line {line}: bad frame count"),
+			ScriptError::BadInput { line, symbol } => {
+				write!(f, "line {line}: unexpected `{symbol}`")
+			}
+			ScriptError::Empty => write!(f, "script has no inputs"),
+		}
+	}
+}
+
+impl std::error::Error for ScriptError {}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn parse() {
+		let script: InputScript = "# warm up\n2 >\n\n>A # move normal\n0 S\n3 ."
+			.parse()
+			.unwrap();
+		assert_eq!(script.len(), 6);
+
+		let forward = FgInput::new(1, false, false);
+		let inputs: Vec<_> = (0..7).map(|frame| script.input(frame)).collect();
+		assert_eq!(
+			inputs,
+			[
+				forward,
+				forward,
+				FgInput::new(1, true, false),
+				FgInput::default(),
+				FgInput::default(),
+				FgInput::default(),
+				forward,
+			]
+		);
+
+		assert_eq!(
+			InputScript::parse("<>"),
+			Err(ScriptError::BadInput {
+				line: 1,
+				symbol: '>'
+			})
+		);
+		assert_eq!(
+			InputScript::parse(".\nx A"),
+			Err(ScriptError::BadCount { line: 2 })
+		);
+		assert_eq!(InputScript::parse("# nothing"), Err(ScriptError::Empty));
+	}
+}
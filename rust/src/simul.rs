@@ -1,60 +1,137 @@
-use crate::{cbox::CBox, input::FgInput, player::Player, timer::Timer};
-use godot::prelude::*;
+use crate::{
+	analysis,
+	cbox::{CBox, WorldBox},
+	character::CharacterId,
+	config::{ConfigError, MatchConfig},
+	desync,
+	event::{self, Attack, Event, PlayerId},
+	framedata::MoveData,
+	input::FgInput,
+	player::{Player, PlayerState},
+	sound::{SoundCue, SoundEvent},
+	timer::Timer,
+	training::{PositionPreset, Training},
+};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, GodotClass, Serialize, Deserialize)]
-#[class(no_init)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Match {
+	config: MatchConfig,
 	timer: Timer,
 	rounds: u8,
 	player1: Player,
 	player2: Player,
 	state: GameState,
+	training: Option<Training>,
+	/// Derived from the rest of the state, so not part of it.
+	#[serde(skip)]
+	events: Vec<Event>,
 }
 
-#[godot_api]
 impl Match {
-	pub const STAGE_LEN: i16 = 1530;
-	const PLAYER_START: i16 = 400;
-	#[allow(unused)]
-	const ROUND_TIME: u16 = 3600;
-	const ROUND_START_LEN: u8 = 90;
-	const HITSTOP_LEN: u8 = 15;
-	const ROUND_END_LEN: u8 = 60;
+	pub const STATE_MAGIC: [u8; 4] = *b"FSMS";
+	/// Bumped whenever the serialized layout of [`Match`] changes.
+	pub const STATE_VERSION: u16 = 6;
 
-	#[func]
-	pub fn gd_new(p1_bot: bool, p2_bot: bool) -> Gd<Self> {
-		Gd::from_object(Self::new(p1_bot, p2_bot))
+	pub const fn new(p1_bot: bool, p2_bot: bool) -> Self {
+		Self::with_characters(p1_bot, p2_bot, CharacterId::DEFAULT, CharacterId::DEFAULT)
 	}
 
-	pub const fn new(p1_bot: bool, p2_bot: bool) -> Self {
+	pub const fn with_characters(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: CharacterId,
+		p2_char: CharacterId,
+	) -> Self {
+		Self::with_valid_config(p1_bot, p2_bot, p1_char, p2_char, MatchConfig::DEFAULT)
+	}
+
+	/// # Panics
+	///
+	/// If `config` does not pass [`MatchConfig::validate`], as the match would not run.
+	pub fn with_config(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: CharacterId,
+		p2_char: CharacterId,
+		config: MatchConfig,
+	) -> Self {
+		if let Err(err) = config.validate() {
+			panic!("invalid match config: {err}");
+		}
+
+		Self::with_valid_config(p1_bot, p2_bot, p1_char, p2_char, config)
+	}
+
+	const fn with_valid_config(
+		p1_bot: bool,
+		p2_bot: bool,
+		p1_char: CharacterId,
+		p2_char: CharacterId,
+		config: MatchConfig,
+	) -> Self {
 		Match {
-			timer: Timer::Limited(Self::ROUND_TIME),
-			//timer: Timer::Unlimited,
+			config,
+			timer: config.round_time,
 			rounds: 0,
-			player1: Player::new(Self::starting_position(true), p1_bot),
-			player2: Player::new(Self::starting_position(false), p2_bot),
-			state: GameState::RoundStart(Self::ROUND_START_LEN),
+			player1: Player::new(config.start(true), p1_bot, p1_char),
+			player2: Player::new(config.start(false), p2_bot, p2_char),
+			state: GameState::RoundStart(config.round_start_len),
+			training: None,
+			events: Vec::new(),
 		}
 	}
 
-	#[func]
+	/// Turns the match into a training session with an unlimited timer.
+	pub fn with_training(mut self, training: Training) -> Self {
+		self.config.round_time = Timer::Unlimited;
+		self.timer = Timer::Unlimited;
+		self.training = Some(training);
+
+		self
+	}
+
 	pub fn new_round(&mut self) {
-		self.player1.reset(Self::starting_position(true));
-		self.player2.reset(Self::starting_position(false));
+		self.player1.reset(&self.config, true);
+		self.player2.reset(&self.config, false);
 
 		*self = Match {
 			player1: self.player1.clone(),
 			player2: self.player2.clone(),
-			// p1_bot and p2_bot does not matter
-			..Match::new(false, false)
+			training: self.training.take(),
+			events: std::mem::take(&mut self.events),
+			// p1_bot, p2_bot and characters does not matter
+			..Match::with_valid_config(
+				false,
+				false,
+				CharacterId::DEFAULT,
+				CharacterId::DEFAULT,
+				self.config,
+			)
 		};
 	}
 
-	#[func]
-	pub fn frame_update(&mut self, input1: Gd<FgInput>, input2: Gd<FgInput>) -> Result {
-		let input1 = *input1.bind();
-		let input2 = *input2.bind();
+	/// [`Match::frame_update`] followed by [`Match::new_round`] once a round is over, which is how
+	/// the game loop runs a frame. Whether the match [`continues`](Match::continues) is left to the
+	/// caller.
+	pub fn step(&mut self, input1: FgInput, input2: FgInput) -> Result {
+		let res = self.frame_update(input1, input2);
+
+		if !matches!(res, Result::Continue | Result::Pause) {
+			self.new_round();
+		}
+
+		res
+	}
+
+	pub fn frame_update(&mut self, input1: FgInput, input2: FgInput) -> Result {
+		self.events.clear();
+
+		let input2 = match &mut self.training {
+			Some(training) => training.dummy.input(input2),
+			None => input2,
+		};
 
 		self.player1.set_input(input1);
 		self.player2.set_input(input2);
@@ -79,9 +156,7 @@ impl Match {
 		self.state = self.state.step();
 
 		if matches!(self.state, GameState::RoundFinish) {
-			let res = self.end_result();
-			self.update_wins(res);
-			return res;
+			return self.finish_round();
 		}
 
 		if !matches!(self.state, GameState::Active) {
@@ -92,15 +167,16 @@ impl Match {
 
 		self.combat_update();
 
-		if self.player1.is_dead() || self.player2.is_dead() {
-			self.state = GameState::RoundEnd(Self::ROUND_END_LEN);
+		if let Some(training) = &mut self.training {
+			training.update(&mut self.player1, &mut self.player2);
+		} else if self.player1.is_dead() || self.player2.is_dead() {
+			self.state = GameState::RoundEnd(self.config.round_end_len);
 			return Result::Continue;
 		}
 
 		if self.timer.is_over() {
-			let res = self.end_result();
-			self.update_wins(res);
-			return res;
+			self.events.push(Event::Timeout);
+			return self.finish_round();
 		}
 
 		Result::Continue
@@ -115,15 +191,22 @@ impl Match {
 		let p1_move = self.player1.update_move();
 		let p2_move = self.player2.update_move();
 
+		event::push_move_events(&mut self.events, &self.player1, PlayerId::P1);
+		event::push_move_events(&mut self.events, &self.player2, PlayerId::P2);
+
 		self.meter_update(p1_move.data.meter, p2_move.data.meter);
 
 		// Update movement
-		self.position_update(p1_move.data.speed, p2_move.data.speed);
+		self.position_update(
+			self.move_speed(true, p1_move),
+			self.move_speed(false, p2_move),
+		);
 
 		// Update char collision
 		self.collision_update(p1_move.data.collision, p2_move.data.collision);
 
-		let p2_hit = if !self.player1.get_hit() {
+		// Knocked down players only see active frames in training, and stay down until they recover.
+		let p2_hit = if !self.player1.get_hit() && !self.player2.is_dead() {
 			Self::hitbox_hurtbox_collision(
 				&p1_move.data.hitbox,
 				&p2_move.data.hurtbox,
@@ -134,7 +217,7 @@ impl Match {
 		} else {
 			false
 		};
-		let p1_hit = if !self.player2.get_hit() {
+		let p1_hit = if !self.player2.get_hit() && !self.player1.is_dead() {
 			Self::hitbox_hurtbox_collision(
 				&p2_move.data.hitbox,
 				&p1_move.data.hurtbox,
@@ -146,32 +229,147 @@ impl Match {
 			false
 		};
 
-		if p2_hit {
-			self.player2.get_attacked(self.player1.is_special());
-			self.player1.set_hit();
-		}
-		if p1_hit {
-			self.player1.get_attacked(self.player2.is_special());
-			self.player2.set_hit();
+		// Both contacts are decided before either one lands, blocking does not depend on the order.
+		let p2_contact = p2_hit.then(|| self.contact(false));
+		let p1_contact = p1_hit.then(|| self.contact(true));
+		let p1_attack = event::Attack::from_state(self.player1.state());
+		let p2_attack = event::Attack::from_state(self.player2.state());
+
+		let p2_hit = self.land(PlayerId::P2, p2_contact, p1_attack);
+		let p1_hit = self.land(PlayerId::P1, p1_contact, p2_attack);
+
+		if let (true, true, Some(p1_attack), Some(p2_attack)) =
+			(p1_hit, p2_hit, p1_attack, p2_attack)
+		{
+			self.events.push(Event::Trade {
+				p1_attack,
+				p2_attack,
+			});
 		}
 
 		self.state = if p2_hit || p1_hit {
-			GameState::Hitstop(Self::HITSTOP_LEN)
+			GameState::Hitstop(self.config.hitstop_len)
 		} else {
 			self.state
 		}
 	}
 
+	/// How an attack reaching the hurtbox of player 1 or player 2 connects.
+	fn contact(&self, p1: bool) -> Contact {
+		let defender = self.player(p1);
+		let Some(guard) = self.config.guard else {
+			return Contact::Hit;
+		};
+		if !defender.is_blocking() {
+			return Contact::Hit;
+		}
+
+		let damage = guard.damage(self.player(!p1).is_special());
+		match defender.guard_damage().saturating_add(damage) >= guard.guard_max {
+			true => Contact::GuardBreak,
+			false => Contact::Block {
+				len: guard.blockstun_len,
+				damage,
+			},
+		}
+	}
+
+	/// Applies the contact of an attack on `defender`. Returns whether the attack hit.
+	fn land(
+		&mut self,
+		defender: PlayerId,
+		contact: Option<Contact>,
+		attack: Option<Attack>,
+	) -> bool {
+		let Some(contact) = contact else {
+			return false;
+		};
+		let (target, attacker) = match defender {
+			PlayerId::P1 => (&mut self.player1, &mut self.player2),
+			PlayerId::P2 => (&mut self.player2, &mut self.player1),
+		};
+
+		attacker.set_hit();
+		match contact {
+			Contact::Hit => (),
+			Contact::Block { len, damage } => {
+				target.block(len, damage);
+				if let Some(attack) = attack {
+					self.events.push(Event::Block {
+						attacker: defender.opponent(),
+						defender,
+						attack,
+					});
+				}
+
+				return false;
+			}
+			Contact::GuardBreak => {
+				target.break_guard();
+				self.events.push(Event::GuardBreak { player: defender });
+			}
+		}
+
+		let damage = attacker.get_move().data.damage;
+		match self.config.health {
+			Some(health)
+				if target.damage().saturating_add(damage) < health.max_health =>
+			{
+				target.stagger(health.hitstun_len, damage);
+			}
+			Some(_) => {
+				target.take_damage(damage);
+				target.get_attacked(attacker.is_special());
+			}
+			None => target.get_attacked(attacker.is_special()),
+		}
+		// Only attacks have hitboxes.
+		if let Some(attack) = attack {
+			self.events.push(Event::Hit {
+				attacker: defender.opponent(),
+				defender,
+				attack,
+			});
+		}
+
+		true
+	}
+
+	/// Blockstun and hitstun push back at the speed of the rules instead of the frame data.
+	#[inline]
+	fn move_speed(&self, p1: bool, data: &MoveData) -> i16 {
+		match (
+			self.player(p1).state(),
+			self.config.guard,
+			self.config.health,
+		) {
+			(PlayerState::Block(_, _), Some(guard), _) => -guard.pushback,
+			(PlayerState::Hitstun(_, _), _, Some(health)) => -health.knockback,
+			_ => data.data.speed,
+		}
+	}
+
 	#[inline]
 	fn meter_update(&mut self, p1_amount: u16, p2_amount: u16) {
-		self.player1.inc_meter(p1_amount);
-		self.player2.inc_meter(p2_amount);
+		for (player, id, amount) in [
+			(&mut self.player1, PlayerId::P1, p1_amount),
+			(&mut self.player2, PlayerId::P2, p2_amount),
+		] {
+			let full = player.meter == 1000;
+			player.inc_meter(amount);
+
+			if !full && player.meter == 1000 {
+				self.events.push(Event::MeterFull { player: id });
+			}
+		}
 	}
 
 	#[inline]
 	fn position_update(&mut self, p1_move: i16, p2_move: i16) {
-		self.player1.move_position(p1_move);
-		self.player2.move_position(-p2_move);
+		let stage_len = self.config.stage_len;
+
+		self.player1.move_position(p1_move, stage_len);
+		self.player2.move_position(-p2_move, stage_len);
 	}
 
 	#[inline]
@@ -182,18 +380,17 @@ impl Match {
 		if p1_col.overlap(offsetx1, -p2_col, offsetx2) {
 			let amount = p1_col.overlap_amount(offsetx1, -p2_col, offsetx2);
 
-			self.player1.move_position(-amount);
-			self.player2.move_position(amount);
+			self.player1.move_position(-amount, self.config.stage_len);
+			self.player2.move_position(amount, self.config.stage_len);
 		}
 	}
 
-	#[inline]
-	const fn starting_position(player1: bool) -> i16 {
-		if player1 {
-			Self::PLAYER_START
-		} else {
-			Self::STAGE_LEN - Self::PLAYER_START
-		}
+	fn finish_round(&mut self) -> Result {
+		let res = self.end_result();
+		self.update_wins(res);
+		self.events.push(Event::RoundEnded { result: res });
+
+		res
 	}
 
 	#[inline]
@@ -212,10 +409,13 @@ impl Match {
 			return Result::Player1;
 		}
 
-		match self
+		// Without health rules both have none left.
+		let health = self.health_left(true).cmp(&self.health_left(false));
+		let position = self
 			.player_relative_pos(true)
-			.cmp(&self.player_relative_pos(false))
-		{
+			.cmp(&self.player_relative_pos(false));
+
+		match health.then(position) {
 			std::cmp::Ordering::Greater => Result::Player1,
 			std::cmp::Ordering::Less => Result::Player2,
 			std::cmp::Ordering::Equal => Result::Draw,
@@ -264,62 +464,87 @@ impl Match {
 		})
 	}
 
-	#[func]
 	#[inline]
 	pub fn player_relative_pos(&self, p1: bool) -> i16 {
 		match p1 {
 			true => self.player1.position,
-			false => Self::STAGE_LEN - self.player2.position,
+			false => self.config.stage_len - self.player2.position,
 		}
 	}
 
-	#[func]
 	#[inline]
 	pub fn player_distance(&self) -> i16 {
 		(self.player1.position - self.player2.position).abs()
 	}
 
-	#[func]
 	pub fn p1_pos(&self) -> i16 {
 		self.player1.position
 	}
 
-	#[func]
 	pub fn p2_pos(&self) -> i16 {
 		self.player2.position
 	}
 
-	#[func]
-	pub fn p1_sprite(&self) -> GString {
-		GString::from(self.player1.get_move().animation_frame)
+	/// Collision box of the current frame in stage coordinates.
+	pub fn collision_box(&self, p1: bool) -> WorldBox {
+		let player = self.player(p1);
+
+		player.get_move().data.collision.world(player.position, !p1)
+	}
+
+	/// Hurtboxes of the current frame in stage coordinates.
+	pub fn hurtboxes(&self, p1: bool) -> impl Iterator<Item = WorldBox> + '_ {
+		let player = self.player(p1);
+
+		player.get_move()
+			.data
+			.hurtbox
+			.iter()
+			.flatten()
+			.map(move |hurtbox| hurtbox.world(player.position, !p1))
+	}
+
+	/// Hitbox of the current frame in stage coordinates.
+	pub fn hitbox(&self, p1: bool) -> Option<WorldBox> {
+		let player = self.player(p1);
+
+		player.get_move()
+			.data
+			.hitbox
+			.map(|hitbox| hitbox.world(player.position, !p1))
+	}
+
+	pub fn p1_sprite(&self) -> &'static str {
+		&self.player1.get_move().animation_frame
 	}
 
-	#[func]
-	pub fn p2_sprite(&self) -> GString {
-		GString::from(self.player2.get_move().animation_frame)
+	pub fn p2_sprite(&self) -> &'static str {
+		&self.player2.get_move().animation_frame
 	}
 
-	#[func]
 	pub fn p1_wins(&self) -> u8 {
 		self.player1.wins
 	}
 
-	#[func]
 	pub fn p2_wins(&self) -> u8 {
 		self.player2.wins
 	}
 
-	#[func]
 	pub fn p1_meter(&self) -> u16 {
 		self.player1.meter
 	}
 
-	#[func]
 	pub fn p2_meter(&self) -> u16 {
 		self.player2.meter
 	}
 
-	#[func]
+	pub fn player_character(&self, p1: bool) -> CharacterId {
+		match p1 {
+			true => self.player1.character(),
+			false => self.player2.character(),
+		}
+	}
+
 	pub fn player_state(&self, p1: bool) -> i64 {
 		match p1 {
 			true => self.player1.state_int(),
@@ -327,7 +552,6 @@ impl Match {
 		}
 	}
 
-	#[func]
 	pub fn player_state_len(&self, p1: bool) -> i64 {
 		match p1 {
 			true => self.player1.state_len(),
@@ -335,16 +559,10 @@ impl Match {
 		}
 	}
 
-	#[func]
 	pub fn player_dead(&self, p1: bool) -> bool {
 		// Prevent spamming on hitstop, round end and round finish
-		match self.state {
-			GameState::Hitstop(..Self::HITSTOP_LEN)
-			| GameState::RoundEnd(..Self::ROUND_END_LEN)
-			| GameState::RoundFinish => {
-				return false;
-			}
-			_ => (),
+		if self.after_hit_or_round_end() {
+			return false;
 		}
 
 		match p1 {
@@ -353,160 +571,263 @@ impl Match {
 		}
 	}
 
-	#[func]
+	/// Guard points left this round, `0` without guard rules.
+	pub fn guard_left(&self, p1: bool) -> u8 {
+		match self.config.guard {
+			Some(guard) => guard
+				.guard_max
+				.saturating_sub(self.player(p1).guard_damage()),
+			None => 0,
+		}
+	}
+
+	/// Health left this round, `0` without health rules.
+	pub fn health_left(&self, p1: bool) -> u16 {
+		match self.config.health {
+			Some(health) => health.max_health.saturating_sub(self.player(p1).damage()),
+			None => 0,
+		}
+	}
+
 	pub fn timer_sec(&self) -> u16 {
 		self.timer.seconds()
 	}
 
-	#[func]
 	pub fn rounds(&self) -> u8 {
 		self.rounds
 	}
 
-	#[func]
-	pub fn audio(&self) -> Vec<GString> {
+	pub fn audio(&self) -> Vec<SoundEvent> {
 		let mut res = Vec::new();
 
 		// Prevent audio spamming on hitstop, round end and round finish
-		match self.state {
-			GameState::Hitstop(..Self::HITSTOP_LEN)
-			| GameState::RoundEnd(..Self::ROUND_END_LEN)
-			| GameState::RoundFinish => {
-				return res;
-			}
-			_ => (),
+		if self.after_hit_or_round_end() {
+			return res;
 		}
 
-		// `x:` x is for the player id. This is to play the same audio for both players on the same frame.
-		if let Some(audio) = self.player1.get_audio() {
-			res.push(GString::from("1:".to_owned() + audio));
-		};
-		if let Some(audio) = self.player2.get_audio() {
-			res.push(GString::from("2:".to_owned() + audio));
-		};
+		for (player, id) in [(&self.player1, PlayerId::P1), (&self.player2, PlayerId::P2)] {
+			if let Some(cue) = player.sound_cue() {
+				res.push(SoundEvent { player: id, cue });
+			}
+		}
+		for event in &self.events {
+			if let Event::GuardBreak { player } = *event {
+				res.push(SoundEvent {
+					player,
+					cue: SoundCue::GuardBreak,
+				});
+			}
+		}
 
 		res
 	}
 
-	#[func]
+	/// What happened during the last [`Match::frame_update`], in order.
+	#[inline]
+	pub fn events(&self) -> &[Event] {
+		&self.events
+	}
+
 	pub fn continues(&self) -> bool {
-		self.player1.wins < 3 && self.player2.wins < 3
+		let wins = self.config.wins_needed;
+
+		self.player1.wins < wins && self.player2.wins < wins
+	}
+
+	/// Past the first frame of hitstop or the round end.
+	#[inline]
+	fn after_hit_or_round_end(&self) -> bool {
+		match self.state {
+			GameState::Hitstop(f) => f < self.config.hitstop_len,
+			GameState::RoundEnd(f) => f < self.config.round_end_len,
+			GameState::RoundFinish => true,
+			_ => false,
+		}
+	}
+
+	#[inline]
+	pub const fn config(&self) -> &MatchConfig {
+		&self.config
+	}
+
+	#[inline]
+	pub const fn training(&self) -> Option<&Training> {
+		self.training.as_ref()
+	}
+
+	/// Changes training settings in place, `None` outside of training.
+	#[inline]
+	pub const fn training_mut(&mut self) -> Option<&mut Training> {
+		self.training.as_mut()
+	}
+
+	/// Puts both players back on their feet at a preset and resumes play right away. Meant for
+	/// training, in a regular match this skips the rest of the round start or round end.
+	pub fn reset_positions(&mut self, preset: PositionPreset) {
+		let config = &self.config;
+		let distance = config.p2_start - config.p1_start;
+		let (p1, p2) = match preset {
+			PositionPreset::MidScreen => (config.p1_start, config.p2_start),
+			PositionPreset::P1Corner => (0, distance),
+			PositionPreset::P2Corner => (config.stage_len - distance, config.stage_len),
+		};
+
+		self.player1.respawn(p1);
+		self.player2.respawn(p2);
+		self.state = GameState::Active;
+
+		if let Some(training) = &mut self.training {
+			training.reset();
+		}
+	}
+
+	#[inline]
+	pub const fn player(&self, p1: bool) -> &Player {
+		match p1 {
+			true => &self.player1,
+			false => &self.player2,
+		}
+	}
+
+	#[inline]
+	pub const fn game_state(&self) -> GameState {
+		self.state
 	}
 
-	#[func]
 	pub fn state(&self) -> i64 {
 		self.state.into()
 	}
 
-	#[func]
 	pub fn state_len(&self) -> i64 {
 		self.state.state_len() as i64
 	}
 
-	#[func]
-	pub fn stage_len() -> i16 {
-		Self::STAGE_LEN
+	#[inline]
+	pub const fn stage_len(&self) -> i16 {
+		self.config.stage_len
+	}
+
+	/// See [`desync::checksum`].
+	#[inline]
+	pub fn checksum(&self) -> u64 {
+		desync::checksum(self)
 	}
 
-	#[func]
 	pub fn serialize_ron(&self) -> String {
 		ron::to_string(self).expect("Could not serialize `Match`.")
 	}
 
-	#[func]
-	pub fn deserialize_ron(&mut self, from: GString) {
-		let new: Self =
-			ron::from_str(&from.to_string()).expect("Could not deserialize `Match`.");
+	pub fn from_ron(from: &str) -> std::result::Result<Self, StateError> {
+		let new: Self = ron::from_str(from)?;
+		new.check_state()?;
 
-		*self = new;
+		Ok(new)
 	}
 
-	#[func]
-	pub fn serialize_bin(&self) -> PackedByteArray {
-		use bincode::{config, serde};
+	/// Leaves the match unchanged on error.
+	pub fn deserialize_ron(&mut self, from: &str) -> std::result::Result<(), StateError> {
+		*self = Self::from_ron(from)?;
 
-		serde::encode_to_vec(self, config::standard())
-			.expect("Could not serialize `Match`.")
-			.into()
+		Ok(())
 	}
 
-	#[func]
-	pub fn deserialize_bin(&mut self, from: PackedByteArray) {
+	/// Bincode encoded state after [`Match::STATE_MAGIC`] and [`Match::STATE_VERSION`].
+	pub fn serialize_bin(&self) -> Vec<u8> {
 		use bincode::{config, serde};
 
-		let (new, _) = serde::borrow_decode_from_slice(&from.to_vec(), config::standard())
-			.expect("Could not deserialize `Match`.");
+		let mut bin = Vec::from(Self::STATE_MAGIC);
+		bin.extend(Self::STATE_VERSION.to_le_bytes());
+		serde::encode_into_std_write(self, &mut bin, config::standard())
+			.expect("Could not serialize `Match`.");
 
-		*self = new;
+		bin
 	}
 
-	#[inline]
-	#[allow(unused)]
-	pub fn can_punish_nnormal(player: &Player, opponent: &Player, inverse: bool) -> bool {
-		const HYPO_ATTACK: CBox = CBox { x: 159 + 140 };
+	pub fn from_bin(from: &[u8]) -> std::result::Result<Self, StateError> {
+		use bincode::{config, serde};
 
-		player.can_attack()
-			&& opponent.recovery_punishable() > 18
-			&& Self::hitbox_hurtbox_collision(
-				&Some(HYPO_ATTACK),
-				&opponent.get_move().data.hurtbox,
-				player.position,
-				opponent.position,
-				inverse,
-			)
+		let Some((magic, from)) = from.split_first_chunk::<4>() else {
+			return Err(StateError::BadHeader);
+		};
+		let Some((version, from)) = from.split_first_chunk::<2>() else {
+			return Err(StateError::BadHeader);
+		};
+		if *magic != Self::STATE_MAGIC {
+			return Err(StateError::BadHeader);
+		}
+		match u16::from_le_bytes(*version) {
+			Self::STATE_VERSION => (),
+			version => return Err(StateError::UnsupportedVersion(version)),
+		}
+
+		let (new, len): (Self, _) = serde::decode_from_slice(from, config::standard())?;
+		if len != from.len() {
+			return Err(StateError::TrailingBytes);
+		}
+		new.check_state()?;
+
+		Ok(new)
 	}
 
-	#[inline]
-	#[allow(unused)]
-	pub fn can_punish_mnormal(player: &Player, opponent: &Player, inverse: bool) -> bool {
-		const HYPO_ATTACK: CBox = CBox { x: 130 + 130 };
+	/// Leaves the match unchanged on error.
+	pub fn deserialize_bin(&mut self, from: &[u8]) -> std::result::Result<(), StateError> {
+		*self = Self::from_bin(from)?;
 
-		player.can_attack()
-			&& opponent.recovery_punishable() > 17
-			&& Self::hitbox_hurtbox_collision(
-				&Some(HYPO_ATTACK),
-				&opponent.get_move().data.hurtbox,
-				player.position,
-				opponent.position,
-				inverse,
-			)
+		Ok(())
 	}
 
-	#[inline]
-	#[allow(unused)]
-	pub fn can_punish_nspecial(player: &Player, opponent: &Player, inverse: bool) -> bool {
-		const HYPO_ATTACK: CBox = CBox { x: 158 + 258 };
+	/// A state from another process may name characters this one never registered.
+	pub(crate) fn check_state(&self) -> std::result::Result<(), StateError> {
+		self.config.validate()?;
 
-		player.can_attack()
-			&& opponent.recovery_punishable() > 25
-			&& Self::hitbox_hurtbox_collision(
-				&Some(HYPO_ATTACK),
-				&opponent.get_move().data.hurtbox,
-				player.position,
-				opponent.position,
-				inverse,
-			)
+		for player in [&self.player1, &self.player2] {
+			if !player.character().is_registered() {
+				return Err(StateError::UnknownCharacter(player.character()));
+			}
+		}
+
+		if self.training
+			.as_ref()
+			.is_some_and(|training| !training.dummy.is_valid())
+		{
+			return Err(StateError::InvalidDummy);
+		}
+
+		Ok(())
 	}
 
-	#[inline]
-	#[allow(unused)]
-	pub fn can_punish_mspecial(player: &Player, opponent: &Player, inverse: bool) -> bool {
-		const HYPO_ATTACK: CBox = CBox { x: 95 + 95 };
+	/// Whether `p1` or player 2 can start `attack` now and hit the opponent before it can act
+	/// again, see [`analysis::can_punish`].
+	pub fn can_punish(&self, p1: bool, attack: Attack) -> bool {
+		let player = self.player(p1);
+		let Some((target, frame)) = self.player(!p1).punishable_move() else {
+			return false;
+		};
 
 		player.can_attack()
-			&& opponent.recovery_punishable() > 16
-			&& Self::hitbox_hurtbox_collision(
-				&Some(HYPO_ATTACK),
-				&opponent.get_move().data.hurtbox,
-				player.position,
-				opponent.position,
-				inverse,
+			&& (!attack.is_special() || player.meter == 1000)
+			&& analysis::can_punish(
+				player.moves().attack(attack),
+				target,
+				frame,
+				self.player_distance(),
 			)
 	}
 }
 
+#[derive(Debug, Clone, Copy)]
+enum Contact {
+	Hit,
+	Block {
+		len: u8,
+		damage: u8,
+	},
+	/// Blocked with too few guard points left, hits anyway.
+	GuardBreak,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-enum GameState {
+pub enum GameState {
 	RoundStart(u8),
 	Active,
 	Hitstop(u8),
@@ -515,6 +836,20 @@ enum GameState {
 }
 
 impl GameState {
+	pub const COUNT: usize = 5;
+
+	/// Dense index of the variant, in `0..GameState::COUNT`.
+	#[inline]
+	pub const fn index(self) -> usize {
+		match self {
+			GameState::RoundStart(_) => 0,
+			GameState::Active => 1,
+			GameState::Hitstop(_) => 2,
+			GameState::RoundEnd(_) => 3,
+			GameState::RoundFinish => 4,
+		}
+	}
+
 	#[inline]
 	pub fn step(self) -> Self {
 		match self {
@@ -575,8 +910,13 @@ impl From<GameState> for i64 {
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, GodotConvert, Var, Export)]
-#[godot(via = i64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "footsies_sim", eq, eq_int))]
 pub enum Result {
 	Continue,
 	Pause,
@@ -585,3 +925,545 @@ pub enum Result {
 	Draw,
 	Timeout,
 }
+
+/// Why a serialized [`Match`] could not be loaded.
+#[derive(Debug)]
+pub enum StateError {
+	BadHeader,
+	/// Serialized by a build with a different state layout.
+	UnsupportedVersion(u16),
+	TrailingBytes,
+	Decode(bincode::error::DecodeError),
+	Parse(ron::error::SpannedError),
+	UnknownCharacter(CharacterId),
+	InvalidConfig(ConfigError),
+	/// The training dummy records into or plays a slot or frame that does not exist.
+	InvalidDummy,
+}
+
+impl StateError {
+	/// Stable number for the error kind, for bindings that can not carry the error itself.
+	pub const fn code(&self) -> i64 {
+		match self {
+			StateError::BadHeader => 1,
+			StateError::UnsupportedVersion(_) => 2,
+			StateError::TrailingBytes => 3,
+			StateError::Decode(_) => 4,
+			StateError::Parse(_) => 5,
+			StateError::UnknownCharacter(_) => 6,
+			StateError::InvalidConfig(_) => 7,
+			StateError::InvalidDummy => 8,
+		}
+	}
+}
+
+impl fmt::Display for StateError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			StateError::BadHeader => write!(f, "not a serialized match state"),
+			StateError::UnsupportedVersion(version) => write!(
+				f,
+				"state version {version} is not supported, expected {}",
+				Match::STATE_VERSION
+			),
+			StateError::TrailingBytes => write!(f, "state has trailing bytes"),
+			StateError::Decode(err) => write!(f, "could not decode state: {err}"),
+			StateError::Parse(err) => write!(f, "could not parse state: {err}"),
+			StateError::UnknownCharacter(id) => {
+				write!(f, "character {} is not registered", id.0)
+			}
+			StateError::InvalidConfig(err) => write!(f, "invalid match config: {err}"),
+			StateError::InvalidDummy => {
+				write!(f, "training dummy state is out of range")
+			}
+		}
+	}
+}
+
+impl std::error::Error for StateError {}
+
+impl From<bincode::error::DecodeError> for StateError {
+	fn from(err: bincode::error::DecodeError) -> Self {
+		StateError::Decode(err)
+	}
+}
+
+impl From<ron::error::SpannedError> for StateError {
+	fn from(err: ron::error::SpannedError) -> Self {
+		StateError::Parse(err)
+	}
+}
+
+impl From<ConfigError> for StateError {
+	fn from(err: ConfigError) -> Self {
+		StateError::InvalidConfig(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		character::register_character,
+		config::{GuardConfig, HealthConfig},
+	};
+
+	const IDLE: FgInput = FgInput::new(0, false, false);
+
+	#[test]
+	#[should_panic(expected = "invalid match config")]
+	fn invalid_config() {
+		let config = MatchConfig {
+			hitstop_len: 0,
+			..MatchConfig::DEFAULT
+		};
+		Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			config,
+		);
+	}
+
+	#[test]
+	fn timeout_draw() {
+		let mut sim = Match::new(false, false);
+
+		let res = loop {
+			match sim.frame_update(IDLE, IDLE) {
+				Result::Continue | Result::Pause => (),
+				res => break res,
+			}
+		};
+
+		assert_eq!(res, Result::Draw);
+		assert_eq!(
+			sim.events(),
+			&[
+				Event::Timeout,
+				Event::RoundEnded {
+					result: Result::Draw
+				}
+			]
+		);
+		assert_eq!(sim.p1_wins(), 1);
+		assert_eq!(sim.p2_wins(), 1);
+		assert!(sim.continues());
+	}
+
+	#[test]
+	fn events() {
+		const ATTACK: FgInput = FgInput::new(0, true, false);
+		let mut sim = Match::new(false, false);
+		while sim.frame_update(IDLE, IDLE) == Result::Pause {}
+
+		// Out of range.
+		let mut events = Vec::new();
+		for frame in 0..60 {
+			let input = if frame == 0 { ATTACK } else { IDLE };
+			sim.frame_update(input, IDLE);
+			events.extend_from_slice(sim.events());
+		}
+		let attack = Attack::NNormal;
+		let player = PlayerId::P1;
+		assert_eq!(
+			events,
+			[
+				Event::MoveStarted { player, attack },
+				Event::HitboxActive { player, attack },
+				Event::Whiff { player, attack },
+			]
+		);
+
+		// Walk in and attack until the round is over.
+		let mut events = Vec::new();
+		let res = loop {
+			let input = match sim.player_distance() {
+				300.. => FgInput::new(1, false, false),
+				_ => ATTACK,
+			};
+			let res = sim.frame_update(input, IDLE);
+			events.extend_from_slice(sim.events());
+
+			if !matches!(res, Result::Continue | Result::Pause) {
+				break res;
+			}
+		};
+
+		assert_eq!(res, Result::Player1);
+		assert!(events.contains(&Event::Hit {
+			attacker: PlayerId::P1,
+			defender: PlayerId::P2,
+			attack,
+		}));
+		assert_eq!(events.last(), Some(&Event::RoundEnded { result: res }));
+
+		// Kept by `new_round`, cleared by the next frame.
+		sim.new_round();
+		assert_eq!(sim.events().len(), 1);
+		sim.frame_update(IDLE, IDLE);
+		assert!(sim.events().is_empty());
+	}
+
+	#[test]
+	fn guard() {
+		let config = MatchConfig {
+			guard: Some(GuardConfig::DEFAULT),
+			..MatchConfig::DEFAULT
+		};
+		let mut sim = Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			config,
+		);
+		assert_eq!(sim.guard_left(false), GuardConfig::DEFAULT.guard_max);
+
+		// Player 2 holds back the whole round, player 1 walks in and keeps attacking.
+		let back = FgInput::new(-1, false, false);
+		let mut events = Vec::new();
+		let mut frame = 0;
+		let res = loop {
+			let input = match sim.player_distance() {
+				300.. => FgInput::new(1, false, false),
+				_ => FgInput::new(0, frame % 2 == 0, false),
+			};
+			frame += 1;
+
+			let res = sim.frame_update(input, back);
+			events.extend_from_slice(sim.events());
+			if sim.events()
+				.iter()
+				.any(|e| matches!(e, Event::Block { .. }))
+			{
+				assert!(sim.audio().iter().any(|s| s.cue == SoundCue::Block));
+			}
+
+			if !matches!(res, Result::Continue | Result::Pause) {
+				break res;
+			}
+		};
+
+		// Two normals take two of the three guard points, the third breaks the guard.
+		let blocked = Event::Block {
+			attacker: PlayerId::P1,
+			defender: PlayerId::P2,
+			attack: Attack::NNormal,
+		};
+		assert_eq!(events.iter().filter(|e| **e == blocked).count(), 2);
+		let guard_break = events
+			.iter()
+			.position(|e| {
+				*e == Event::GuardBreak {
+					player: PlayerId::P2,
+				}
+			})
+			.unwrap();
+		assert!(matches!(
+			events[guard_break + 1],
+			Event::Hit {
+				defender: PlayerId::P2,
+				..
+			}
+		));
+		assert_eq!(res, Result::Player1);
+		assert_eq!(sim.guard_left(false), 0);
+
+		sim.new_round();
+		assert_eq!(sim.guard_left(false), GuardConfig::DEFAULT.guard_max);
+	}
+
+	#[test]
+	fn health() {
+		let config = MatchConfig {
+			health: Some(HealthConfig::DEFAULT),
+			..MatchConfig::DEFAULT
+		};
+		let mut sim = Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			config,
+		);
+		assert_eq!(sim.health_left(false), 100);
+
+		// Walk in and attack until the round is over, it takes one hit per 10 health.
+		let mut hits = 0;
+		let mut frame = 0;
+		let res = loop {
+			let input = match sim.player_distance() {
+				300.. => FgInput::new(1, false, false),
+				_ => FgInput::new(0, frame % 2 == 0, false),
+			};
+			frame += 1;
+
+			let res = sim.frame_update(input, IDLE);
+			if sim.events().iter().any(|e| matches!(e, Event::Hit { .. })) {
+				hits += 1;
+				assert_eq!(sim.health_left(false), 100 - hits * 10);
+				assert_eq!(sim.player(false).is_dead(), hits == 10);
+				if hits == 1 {
+					assert!(matches!(
+						sim.player(false).state(),
+						PlayerState::Hitstun(0, 20)
+					));
+					assert!(sim.audio().iter().any(|s| s.cue == SoundCue::Hit));
+				}
+			}
+
+			if !matches!(res, Result::Continue | Result::Pause) {
+				break res;
+			}
+		};
+
+		assert_eq!(res, Result::Player1);
+		assert_eq!(hits, 10);
+
+		// More health left wins on time.
+		let config = MatchConfig {
+			round_time: Timer::Limited(600),
+			..config
+		};
+		let mut sim = Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			config,
+		);
+		let res = loop {
+			let input = match (sim.player_distance(), sim.health_left(true)) {
+				(300.., _) => FgInput::new(1, false, false),
+				(_, 100) => FgInput::new(0, true, false),
+				_ => FgInput::new(-1, false, false),
+			};
+
+			match sim.frame_update(IDLE, input) {
+				Result::Continue | Result::Pause => (),
+				res => break res,
+			}
+		};
+		assert!(sim.health_left(true) < sim.health_left(false));
+		assert_eq!(res, Result::Player2);
+	}
+
+	#[test]
+	fn punish() {
+		const ATTACKS: [(Attack, FgInput); 4] = [
+			(Attack::NNormal, FgInput::new(0, true, false)),
+			(Attack::MNormal, FgInput::new(1, true, false)),
+			(Attack::NSpecial, FgInput::new(0, false, true)),
+			(Attack::MSpecial, FgInput::new(1, false, true)),
+		];
+		let mut start = Match::new(false, false);
+		while start.frame_update(IDLE, IDLE) == Result::Pause {}
+		start.player1.meter = 1000;
+		start.player2.meter = 1000;
+
+		let mut seen = [false; 2];
+		for distance in (300..800).step_by(50) {
+			for (whiff, whiff_input) in ATTACKS {
+				let mut sim = start.clone();
+				sim.player1.position = 400;
+				sim.player2.position = 400 + distance;
+				sim.frame_update(IDLE, whiff_input);
+
+				while let Some((_, frame)) = sim.player2.punishable_move() {
+					for (attack, input) in ATTACKS {
+						let predicted = sim.can_punish(true, attack);
+						let mut punish = sim.clone();
+						let mut punished = false;
+						let mut input = input;
+						while punish.player2.recovery_punishable() > 0 {
+							punish.frame_update(input, IDLE);
+							input = IDLE;
+
+							let hit = |attacker| {
+								punish.events().iter().any(
+									|event| matches!(event, Event::Hit { attacker: a, .. } if *a == attacker),
+								)
+							};
+							// Trades are no punish.
+							if hit(PlayerId::P2) {
+								punished = false;
+								break;
+							}
+							punished |= hit(PlayerId::P1);
+						}
+
+						assert_eq!(
+							predicted, punished,
+							"{attack:?} on {whiff:?} frame {frame} at {distance}"
+						);
+						seen[predicted as usize] = true;
+					}
+
+					sim.frame_update(IDLE, IDLE);
+					if sim.player1.is_dead() {
+						break;
+					}
+				}
+			}
+		}
+		assert_eq!(seen, [true, true]);
+	}
+
+	#[test]
+	fn boxes() {
+		let mut sim = Match::new(false, false);
+		let world = |x, width| WorldBox {
+			x,
+			y: 0,
+			width,
+			height: CBox::DEFAULT_HEIGHT,
+		};
+		assert_eq!(sim.collision_box(true), world(400, 125));
+		assert_eq!(sim.collision_box(false), world(1005, 125));
+		assert_eq!(sim.hurtboxes(false).collect::<Vec<_>>(), [world(972, 158)]);
+		assert_eq!(sim.hitbox(true), None);
+
+		while sim.frame_update(IDLE, IDLE) == Result::Pause {}
+		while sim.hitbox(false).is_none() {
+			sim.frame_update(IDLE, FgInput::new(0, true, false));
+		}
+		let hitbox = sim.hitbox(false).unwrap();
+		assert_eq!(hitbox.x + hitbox.width, sim.p2_pos());
+	}
+
+	#[test]
+	fn characters() {
+		let mut fast = CharacterId::DEFAULT.get().clone();
+		fast.name = "Fast Walker".to_owned();
+		for frame in &mut fast.moves.fwalk {
+			frame.data.speed = 12;
+		}
+		let fast = register_character(fast).unwrap();
+
+		let mut sim = Match::with_characters(false, false, CharacterId::DEFAULT, fast);
+		while sim.frame_update(IDLE, IDLE) == Result::Pause {}
+		let (p1_start, p2_start) = (sim.p1_pos(), sim.p2_pos());
+
+		let forward = FgInput::new(1, false, false);
+		for _ in 0..10 {
+			sim.frame_update(forward, forward);
+		}
+
+		assert_eq!(sim.player_character(false), fast);
+		assert_eq!(sim.p1_pos() - p1_start, 60);
+		assert_eq!(p2_start - sim.p2_pos(), 120);
+
+		let mut copy = Match::new(false, false);
+		copy.deserialize_bin(&sim.serialize_bin()).unwrap();
+		assert_eq!(copy.player_character(false), fast);
+	}
+
+	#[test]
+	fn serialize_roundtrip() {
+		let mut sim = Match::new(false, false);
+		for _ in 0..120 {
+			sim.frame_update(FgInput::new(1, false, false), IDLE);
+		}
+
+		let mut bin = Match::new(false, false);
+		bin.deserialize_bin(&sim.serialize_bin()).unwrap();
+		assert_eq!(bin.serialize_bin(), sim.serialize_bin());
+
+		let mut ron = Match::new(false, false);
+		ron.deserialize_ron(&sim.serialize_ron()).unwrap();
+		assert_eq!(ron.serialize_ron(), sim.serialize_ron());
+		assert_eq!(ron.p1_pos(), sim.p1_pos());
+	}
+
+	#[test]
+	fn config() {
+		let config = MatchConfig {
+			round_time: Timer::Unlimited,
+			wins_needed: 1,
+			stage_len: 2000,
+			p1_start: 300,
+			p2_start: 1700,
+			..MatchConfig::DEFAULT
+		};
+		let mut sim = Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			config,
+		);
+		assert_eq!((sim.p1_pos(), sim.p2_pos()), (300, 1700));
+
+		for _ in 0..2 * 3600 {
+			assert!(matches!(
+				sim.frame_update(IDLE, IDLE),
+				Result::Continue | Result::Pause
+			));
+		}
+		assert_eq!(sim.timer_sec(), 0);
+
+		// Nobody moves, so the timed out round is a draw and ends the match for both.
+		let mut sim = Match::with_config(
+			false,
+			false,
+			CharacterId::DEFAULT,
+			CharacterId::DEFAULT,
+			MatchConfig {
+				round_time: Timer::Limited(300),
+				..config
+			},
+		);
+		let res = loop {
+			match sim.frame_update(IDLE, IDLE) {
+				Result::Continue | Result::Pause => (),
+				res => break res,
+			}
+		};
+		assert_eq!(res, Result::Draw);
+
+		sim.new_round();
+		assert!(!sim.continues());
+		assert_eq!(sim.config().stage_len, 2000);
+		assert_eq!((sim.p1_pos(), sim.p2_pos()), (300, 1700));
+	}
+
+	#[test]
+	fn deserialize_errors() {
+		let sim = Match::new(false, false);
+		let bin = sim.serialize_bin();
+		let mut target = Match::new(false, false);
+
+		let mut version = bin.clone();
+		version[4] += 1;
+		assert!(matches!(
+			target.deserialize_bin(&version),
+			Err(StateError::UnsupportedVersion(v)) if v == Match::STATE_VERSION + 1
+		));
+		assert!(matches!(
+			Match::from_bin(&bin[..3]),
+			Err(StateError::BadHeader)
+		));
+		assert!(matches!(
+			Match::from_bin(&bin[..bin.len() - 1]),
+			Err(StateError::Decode(_))
+		));
+		assert!(matches!(
+			Match::from_bin(&[&bin[..], &[0]].concat()),
+			Err(StateError::TrailingBytes)
+		));
+		assert!(matches!(
+			target.deserialize_ron("(timer:"),
+			Err(StateError::Parse(_))
+		));
+
+		let unknown = sim
+			.serialize_ron()
+			.replace("character:(0)", "character:(255)");
+		assert!(matches!(
+			Match::from_ron(&unknown),
+			Err(StateError::UnknownCharacter(CharacterId(255)))
+		));
+	}
+}
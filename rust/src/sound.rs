@@ -0,0 +1,95 @@
+//! Sounds to play for a frame, as reported by [`Match::audio`](crate::simul::Match::audio).
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::PlayerId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum SoundCue {
+	FDash,
+	BDash,
+	NNormal,
+	MNormal,
+	NSpecial,
+	MSpecial,
+	EnderHit,
+	Block,
+	GuardBreak,
+	Hit,
+}
+
+impl SoundCue {
+	/// Name of the audio file for the cue, without extension.
+	#[inline]
+	pub const fn name(self) -> &'static str {
+		match self {
+			SoundCue::FDash => "fdash",
+			SoundCue::BDash => "bdash",
+			SoundCue::NNormal => "nnormal",
+			SoundCue::MNormal => "mnormal",
+			SoundCue::NSpecial => "nspecial",
+			SoundCue::MSpecial => "mspecial",
+			SoundCue::EnderHit => "ender_hit",
+			SoundCue::Block => "block",
+			SoundCue::GuardBreak => "guard_break",
+			SoundCue::Hit => "hit",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SoundEvent {
+	pub player: PlayerId,
+	pub cue: SoundCue,
+}
+
+/// `1:nnormal` for [`SoundCue::NNormal`] of player 1. Unique within a frame, so it can key the
+/// sound when both players play the same cue.
+impl fmt::Display for SoundEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let player = match self.player {
+			PlayerId::P1 => 1,
+			PlayerId::P2 => 2,
+		};
+
+		write!(f, "{player}:{}", self.cue.name())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		input::FgInput,
+		simul::{Match, Result},
+	};
+
+	#[test]
+	fn audio() {
+		const IDLE: FgInput = FgInput::new(0, false, false);
+		let mut sim = Match::new(false, false);
+		while sim.frame_update(IDLE, IDLE) == Result::Pause {}
+
+		let attack = FgInput::new(0, true, false);
+		sim.frame_update(attack, attack);
+		let audio = sim.audio();
+		assert_eq!(
+			audio,
+			[PlayerId::P1, PlayerId::P2].map(|player| SoundEvent {
+				player,
+				cue: SoundCue::NNormal
+			})
+		);
+		assert_eq!(audio[1].to_string(), "2:nnormal");
+
+		sim.frame_update(IDLE, IDLE);
+		assert!(sim.audio().is_empty());
+	}
+}
@@ -0,0 +1,444 @@
+//! Practice mode. A match in training never ends a round: the timer is off, knocked down players
+//! get back up on their own and positions can be reset at any time.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{input::FgInput, player::Player, rng::Rng};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Training {
+	/// Keeps the meter of player 1 and player 2 full.
+	pub lock_meter: [bool; 2],
+	/// Frames a knocked down player stays down before getting back up.
+	pub recovery_frames: u8,
+	/// Records and plays back the inputs of player 2.
+	pub dummy: Dummy,
+	/// Frames each player has been down for.
+	down: [u8; 2],
+}
+
+impl Training {
+	pub const DEFAULT_RECOVERY_FRAMES: u8 = 60;
+
+	pub const fn new() -> Self {
+		Training {
+			lock_meter: [false; 2],
+			recovery_frames: Self::DEFAULT_RECOVERY_FRAMES,
+			dummy: Dummy::new(),
+			down: [0; 2],
+		}
+	}
+
+	/// Runs at the end of every active frame, in place of ending the round.
+	pub(crate) fn update(&mut self, player1: &mut Player, player2: &mut Player) {
+		for (index, player) in [player1, player2].into_iter().enumerate() {
+			if player.is_dead() {
+				self.down[index] += 1;
+
+				if self.down[index] >= self.recovery_frames {
+					player.respawn(player.position);
+					self.down[index] = 0;
+
+					if index == 1 {
+						self.dummy.trigger(PlaybackTrigger::Wakeup);
+					}
+				}
+			}
+
+			if self.lock_meter[index] {
+				player.meter = 1000;
+			}
+		}
+	}
+
+	/// Positions were reset.
+	pub(crate) fn reset(&mut self) {
+		self.down = [0; 2];
+		self.dummy.trigger(PlaybackTrigger::Reset);
+	}
+}
+
+impl Default for Training {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Input slots for player 2. A recording captures whatever input player 2 sends, so the game only
+/// has to route a controller to player 2 while recording. Playback replaces the input of player 2
+/// with a slot, frame by frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dummy {
+	/// Slot played back when `random_slot` is off.
+	pub playback_slot: u8,
+	/// Picks a random recorded slot each time playback starts.
+	pub random_slot: bool,
+	slots: [Vec<FgInput>; Dummy::SLOT_COUNT],
+	mode: DummyMode,
+	rng: Rng,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum DummyMode {
+	/// Player 2 input goes through.
+	Off,
+	Recording {
+		slot: u8,
+		left: u16,
+	},
+	/// Waiting for the trigger to start playing.
+	Armed(PlaybackTrigger),
+	Playing {
+		trigger: PlaybackTrigger,
+		slot: u8,
+		frame: u16,
+	},
+}
+
+impl Dummy {
+	pub const SLOT_COUNT: usize = 5;
+
+	pub const fn new() -> Self {
+		Dummy {
+			playback_slot: 0,
+			random_slot: false,
+			slots: [const { Vec::new() }; Self::SLOT_COUNT],
+			mode: DummyMode::Off,
+			rng: Rng::new(0),
+		}
+	}
+
+	/// Replaces `slot` with the next `frames` inputs of player 2.
+	pub fn record(&mut self, slot: u8, frames: u16) {
+		assert!(
+			(slot as usize) < Self::SLOT_COUNT,
+			"Dummy slot {slot} does not exist."
+		);
+
+		self.slots[slot as usize].clear();
+		self.mode = match frames {
+			0 => DummyMode::Off,
+			left => DummyMode::Recording { slot, left },
+		};
+	}
+
+	/// Starts playing right away for [`PlaybackTrigger::Loop`], otherwise on the next trigger.
+	/// Returns `false` if there is no recorded slot to play.
+	pub fn play(&mut self, trigger: PlaybackTrigger) -> bool {
+		if self.pick_slot().is_none() {
+			return false;
+		}
+
+		self.mode = DummyMode::Armed(trigger);
+		if trigger == PlaybackTrigger::Loop {
+			self.trigger(PlaybackTrigger::Loop);
+		}
+
+		true
+	}
+
+	/// Stops recording or playback and gives control back to player 2.
+	pub fn stop(&mut self) {
+		self.mode = DummyMode::Off;
+	}
+
+	/// Recorded inputs of `slot`, `None` if the slot does not exist.
+	#[inline]
+	pub fn slot(&self, slot: u8) -> Option<&[FgInput]> {
+		self.slots.get(slot as usize).map(Vec::as_slice)
+	}
+
+	/// Replaces the inputs of `slot`. Returns `false` if the slot does not exist.
+	pub fn set_slot(&mut self, slot: u8, inputs: Vec<FgInput>) -> bool {
+		let Some(dest) = self.slots.get_mut(slot as usize) else {
+			return false;
+		};
+
+		*dest = inputs;
+		true
+	}
+
+	#[inline]
+	pub fn is_recording(&self) -> bool {
+		matches!(self.mode, DummyMode::Recording { .. })
+	}
+
+	#[inline]
+	pub fn is_playing(&self) -> bool {
+		matches!(self.mode, DummyMode::Playing { .. })
+	}
+
+	/// Whether the mode points at a recorded frame or a slot that exists, as it always does unless
+	/// the dummy comes from a hand-edited state.
+	pub(crate) fn is_valid(&self) -> bool {
+		match self.mode {
+			DummyMode::Off | DummyMode::Armed(_) => true,
+			DummyMode::Recording { slot, left } => {
+				(slot as usize) < Self::SLOT_COUNT && left > 0
+			}
+			DummyMode::Playing { slot, frame, .. } => self
+				.slot(slot)
+				.is_some_and(|inputs| (frame as usize) < inputs.len()),
+		}
+	}
+
+	/// Input player 2 actually uses this frame. A mode that is not [valid](Self::is_valid) counts
+	/// as off.
+	pub(crate) fn input(&mut self, input: FgInput) -> FgInput {
+		if !self.is_valid() {
+			self.mode = DummyMode::Off;
+		}
+
+		match self.mode {
+			DummyMode::Off | DummyMode::Armed(_) => input,
+			DummyMode::Recording { slot, left } => {
+				self.slots[slot as usize].push(input);
+				self.mode = match left - 1 {
+					0 => DummyMode::Off,
+					left => DummyMode::Recording { slot, left },
+				};
+
+				input
+			}
+			DummyMode::Playing {
+				trigger,
+				slot,
+				frame,
+			} => {
+				let inputs = &self.slots[slot as usize];
+				let res = inputs[frame as usize];
+
+				if frame as usize + 1 < inputs.len() {
+					self.mode = DummyMode::Playing {
+						trigger,
+						slot,
+						frame: frame + 1,
+					};
+				} else {
+					self.mode = DummyMode::Armed(trigger);
+					self.trigger(PlaybackTrigger::Loop);
+				}
+
+				res
+			}
+		}
+	}
+
+	/// Starts playing from the top if armed for or playing with `trigger`.
+	fn trigger(&mut self, trigger: PlaybackTrigger) {
+		let (DummyMode::Armed(armed) | DummyMode::Playing { trigger: armed, .. }) =
+			self.mode
+		else {
+			return;
+		};
+		if armed != trigger {
+			return;
+		}
+
+		self.mode = match self.pick_slot() {
+			Some(slot) => DummyMode::Playing {
+				trigger,
+				slot,
+				frame: 0,
+			},
+			None => DummyMode::Off,
+		};
+	}
+
+	fn pick_slot(&mut self) -> Option<u8> {
+		let is_recorded = |slot: &u8| {
+			self.slots
+				.get(*slot as usize)
+				.is_some_and(|inputs| !inputs.is_empty())
+		};
+
+		if !self.random_slot {
+			return Some(self.playback_slot).filter(is_recorded);
+		}
+
+		let slots = 0..Self::SLOT_COUNT as u8;
+		let pick = match slots.clone().filter(is_recorded).count() as u32 {
+			0 => return None,
+			count => self.rng.below(count),
+		};
+
+		slots.filter(is_recorded).nth(pick as usize)
+	}
+}
+
+impl Default for Dummy {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// When [`Dummy`] playback starts over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum PlaybackTrigger {
+	/// Over and over, back to back.
+	Loop,
+	/// Whenever player 2 gets back up after a knockdown.
+	Wakeup,
+	/// Whenever positions are reset.
+	Reset,
+}
+
+/// Where [`Match::reset_positions`](crate::simul::Match::reset_positions) puts the players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+	feature = "godot",
+	derive(godot::prelude::GodotConvert, godot::prelude::Var, godot::prelude::Export),
+	godot(via = i64)
+)]
+pub enum PositionPreset {
+	/// Round start positions.
+	MidScreen,
+	/// Player 1 with their back to the wall, player 2 at round start distance.
+	P1Corner,
+	P2Corner,
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		config::MatchConfig,
+		input::FgInput,
+		simul::{Match, Result, StateError},
+	};
+
+	const IDLE: FgInput = FgInput::new(0, false, false);
+
+	#[test]
+	fn recovery() {
+		let mut sim = Match::new(false, false).with_training(Training {
+			lock_meter: [false, true],
+			..Training::new()
+		});
+
+		// Walk in and attack until the dummy goes down, then keep going without a round end.
+		let mut knockdowns = 0;
+		for frame in 0..5000 {
+			let input = match sim.player_distance() {
+				300.. => FgInput::new(1, false, false),
+				_ => FgInput::new(0, frame % 2 == 0, false),
+			};
+			assert!(matches!(
+				sim.frame_update(input, IDLE),
+				Result::Continue | Result::Pause
+			));
+
+			if sim.player_dead(false) {
+				knockdowns += 1;
+			}
+		}
+
+		assert!(knockdowns > 1);
+		assert_eq!(sim.p2_meter(), 1000);
+		assert_eq!(sim.timer_sec(), 0);
+
+		sim.reset_positions(PositionPreset::P1Corner);
+		assert_eq!(sim.p1_pos(), 0);
+		assert_eq!(
+			sim.p2_pos(),
+			MatchConfig::DEFAULT.p2_start - MatchConfig::DEFAULT.p1_start
+		);
+		assert!(!sim.player(false).is_dead());
+
+		sim.reset_positions(PositionPreset::MidScreen);
+		assert_eq!(sim.p1_pos(), MatchConfig::DEFAULT.p1_start);
+	}
+
+	#[test]
+	fn dummy() {
+		const WALK: FgInput = FgInput::new(1, false, false);
+		let mut sim = Match::new(false, false).with_training(Training::new());
+		sim.reset_positions(PositionPreset::MidScreen);
+
+		let dummy = &mut sim.training_mut().unwrap().dummy;
+		assert!(!dummy.play(PlaybackTrigger::Loop));
+		dummy.record(0, 30);
+		for _ in 0..40 {
+			sim.frame_update(IDLE, WALK);
+		}
+
+		let dummy = &mut sim.training_mut().unwrap().dummy;
+		assert!(!dummy.is_recording());
+		assert_eq!(dummy.slot(0), Some(&[WALK; 30][..]));
+		assert_eq!(dummy.slot(Dummy::SLOT_COUNT as u8), None);
+
+		// Armed for a reset, player 2 stays put until then and plays the slot once after.
+		assert!(dummy.play(PlaybackTrigger::Reset));
+		let start = sim.p2_pos();
+		for _ in 0..10 {
+			sim.frame_update(IDLE, IDLE);
+		}
+		assert_eq!(sim.p2_pos(), start);
+
+		sim.reset_positions(PositionPreset::MidScreen);
+		let start = sim.p2_pos();
+		for _ in 0..30 {
+			sim.frame_update(IDLE, IDLE);
+		}
+		assert!(sim.p2_pos() < start);
+
+		let walked = sim.p2_pos();
+		for _ in 0..30 {
+			sim.frame_update(IDLE, IDLE);
+		}
+		assert_eq!(sim.p2_pos(), walked);
+
+		let dummy = &mut sim.training_mut().unwrap().dummy;
+		assert!(dummy.set_slot(3, vec![IDLE; 7]));
+		assert!(!dummy.set_slot(Dummy::SLOT_COUNT as u8, vec![IDLE; 7]));
+		dummy.random_slot = true;
+		assert!(dummy.play(PlaybackTrigger::Loop));
+		for _ in 0..100 {
+			sim.frame_update(IDLE, IDLE);
+		}
+		assert!(sim.training().unwrap().dummy.is_playing());
+	}
+
+	#[test]
+	fn invalid_dummy() {
+		const WALK: FgInput = FgInput::new(1, false, false);
+		let mut training = Training::new();
+		training.dummy.mode = DummyMode::Playing {
+			trigger: PlaybackTrigger::Loop,
+			slot: 0,
+			frame: 0,
+		};
+		let mut sim = Match::new(false, false).with_training(training);
+		let state = sim.serialize_ron();
+		assert!(matches!(
+			Match::from_ron(&state),
+			Err(StateError::InvalidDummy)
+		));
+
+		// Played as off rather than panicking.
+		assert_eq!(sim.training_mut().unwrap().dummy.input(WALK), WALK);
+		assert!(!sim.training().unwrap().dummy.is_playing());
+
+		for mode in [
+			DummyMode::Recording { slot: 0, left: 0 },
+			DummyMode::Recording { slot: 5, left: 10 },
+			DummyMode::Playing {
+				trigger: PlaybackTrigger::Loop,
+				slot: 9,
+				frame: 0,
+			},
+		] {
+			let dummy = &mut sim.training_mut().unwrap().dummy;
+			dummy.mode = mode;
+			assert!(!dummy.is_valid());
+			assert!(sim.check_state().is_err());
+			assert_eq!(sim.training_mut().unwrap().dummy.input(WALK), WALK);
+			assert!(sim.check_state().is_ok());
+		}
+	}
+}